@@ -0,0 +1,94 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aincradfs_path::path::{PathBuilder, PathOwned, U8PathBuf};
+use bstr::BStr;
+
+use common::bench;
+
+// Counts allocations so that reserving up front can be checked, not just timed.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations(name: &str, mut f: impl FnMut()) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    println!(
+        "{name}: {} allocations",
+        ALLOCATIONS.load(Ordering::Relaxed) - before
+    );
+}
+
+fn main() {
+    let names = ["mnt", "some", "deeply", "nested", "directory", "structure"]
+        .repeat(8)
+        .into_iter()
+        .map(BStr::new)
+        .collect::<Vec<_>>();
+    let len = names.iter().map(|name| name.len() + 1).sum();
+
+    bench("repeated push", || {
+        let mut path = U8PathBuf::new();
+        path.push(BStr::new("/"));
+        for name in &names {
+            path.push(name);
+        }
+        black_box(path);
+    });
+
+    bench("builder", || {
+        let mut builder = PathBuilder::<U8PathBuf>::with_capacity(len);
+        builder.push_component(BStr::new("/"));
+        for name in &names {
+            builder.push_component(name);
+        }
+        black_box(builder.build());
+    });
+
+    let many = ["mnt", "some", "deeply", "nested", "directory"]
+        .repeat(20)
+        .into_iter()
+        .map(BStr::new)
+        .collect::<Vec<_>>();
+
+    let push_many = || {
+        let mut path = U8PathBuf::from("/");
+        for name in &many {
+            path.push(name);
+        }
+        black_box(path);
+    };
+    let extend_many = || {
+        let mut path = U8PathBuf::from("/");
+        path.extend(many.iter().copied());
+        black_box(path);
+    };
+
+    bench("100 components, repeated push", push_many);
+    bench("100 components, extend", extend_many);
+    allocations("100 components, repeated push", push_many);
+    allocations("100 components, extend", extend_many);
+}
@@ -0,0 +1,16 @@
+use std::time::Instant;
+
+const ITERATIONS: u32 = 10_000;
+
+pub fn bench(name: &str, mut f: impl FnMut()) {
+    // warm up
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    println!("{name}: {:?}/iter", start.elapsed() / ITERATIONS);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use std::hint::black_box;
+
+use aincradfs_path::path::{Path, U16PathBuf, U8Path, U8PathBuf};
+
+use common::bench;
+
+fn main() {
+    let long = "/some_fairly_long_directory_name".repeat(64);
+
+    let u8_path = U8PathBuf::from(long.as_str());
+    bench("u8 components forward", || {
+        black_box(black_box(&u8_path).components().count());
+    });
+    bench("u8 components backward", || {
+        black_box(black_box(&u8_path).components().rev().count());
+    });
+
+    // the comparison U8Path::is_separator used before it became a table lookup
+    let bytes = long.repeat(16).into_bytes();
+    bench("u8 is_separator", || {
+        let bytes = black_box(&bytes);
+        black_box(bytes.iter().filter(|&&b| U8Path::is_separator(b)).count());
+    });
+    bench("u8 is_separator (contains)", || {
+        let bytes = black_box(&bytes);
+        black_box(bytes.iter().filter(|b| [b'/', b'\\'].contains(b)).count());
+    });
+
+    let u16_path = U16PathBuf::from(long.as_str());
+    bench("u16 components forward", || {
+        black_box(black_box(&u16_path).components().count());
+    });
+    bench("u16 components backward", || {
+        black_box(black_box(&u16_path).components().rev().count());
+    });
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::hint::black_box;
+
+use aincradfs_path::path::{CachedPathBuf, Component, Path, PathOwned, U8Path, U8PathBuf};
+
+use common::bench;
+
+// How many components `stack_eq` collects before giving up on the stack buffer.
+const STACK_COMPONENTS: usize = 8;
+
+// Compares paths by collecting both into stack buffers, rather than walking them
+// from the back in step the way `Components::eq` does.
+fn stack_eq(a: &U8Path, b: &U8Path) -> bool {
+    fn collect<'a>(path: &'a U8Path, buf: &mut [Component<'a, U8Path>]) -> Option<usize> {
+        let mut len = 0;
+        for component in path.components() {
+            *buf.get_mut(len)? = component;
+            len += 1;
+        }
+        Some(len)
+    }
+
+    let mut ours = [Component::Root; STACK_COMPONENTS];
+    let mut theirs = [Component::Root; STACK_COMPONENTS];
+    match (collect(a, &mut ours), collect(b, &mut theirs)) {
+        (Some(m), Some(n)) => ours[..m] == theirs[..n],
+        _ => a.components() == b.components(),
+    }
+}
+
+fn main() {
+    let a = "/mnt/some/deeply/nested//directory/./structure/file.txt";
+    let b = "/mnt/some/deeply/nested/directory/structure/file.txt";
+
+    let (plain_a, plain_b) = (U8PathBuf::from(a), U8PathBuf::from(b));
+    bench("uncached repeated eq", || {
+        for _ in 0..16 {
+            black_box(black_box(&plain_a) == black_box(&plain_b));
+        }
+    });
+
+    let (cached_a, cached_b) = (
+        CachedPathBuf::new(U8PathBuf::from(a)),
+        CachedPathBuf::new(U8PathBuf::from(b)),
+    );
+    bench("cached repeated eq", || {
+        for _ in 0..16 {
+            black_box(black_box(&cached_a) == black_box(&cached_b));
+        }
+    });
+
+    let pairs = [
+        ("/a/b", "/a//b"),
+        ("/mnt/disk/file", "/mnt/disk/fila"),
+        ("save/slot1/data.bin", "save/slot2/data.bin"),
+        (a, b),
+    ];
+    let pairs = pairs.map(|(a, b)| (U8PathBuf::from(a), U8PathBuf::from(b)));
+    bench("short iterator eq", || {
+        for (a, b) in &pairs {
+            black_box(black_box(a.as_path()) == black_box(b.as_path()));
+        }
+    });
+    bench("short stack buffer eq", || {
+        for (a, b) in &pairs {
+            black_box(stack_eq(black_box(a), black_box(b)));
+        }
+    });
+
+    let shared = "/mnt/some/deeply/nested/directory/structure".repeat(8);
+    let (long_a, long_b) = (
+        U8PathBuf::from(format!("{shared}/file.txt").as_str()),
+        U8PathBuf::from(format!("{shared}/file.txs").as_str()),
+    );
+    bench("long shared prefix ne", || {
+        for _ in 0..16 {
+            black_box(black_box(&long_a) == black_box(&long_b));
+        }
+    });
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use aincradfs_path::path::{HashedPathBuf, U8PathBuf};
+
+use common::bench;
+
+fn main() {
+    let paths = (0..64)
+        .map(|i| format!("/mnt/some/deeply/nested/directory/structure/file{i}.txt"))
+        .collect::<Vec<_>>();
+
+    let plain = paths
+        .iter()
+        .map(|path| (U8PathBuf::from(path.as_str()), ()))
+        .collect::<HashMap<_, _>>();
+    let key = U8PathBuf::from(paths[42].as_str());
+    bench("uncached repeated lookup", || {
+        for _ in 0..16 {
+            black_box(plain.get(black_box(&key)));
+        }
+    });
+
+    let hashed = paths
+        .iter()
+        .map(|path| (HashedPathBuf::new(U8PathBuf::from(path.as_str())), ()))
+        .collect::<HashMap<_, _>>();
+    let key = HashedPathBuf::new(U8PathBuf::from(paths[42].as_str()));
+    bench("cached repeated lookup", || {
+        for _ in 0..16 {
+            black_box(hashed.get(black_box(&key)));
+        }
+    });
+}
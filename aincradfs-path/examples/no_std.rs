@@ -0,0 +1,25 @@
+//! Compile test for `no_std` builds. Build with
+//! `cargo build --example no_std --no-default-features`.
+#![no_std]
+
+extern crate alloc;
+
+use aincradfs_path::path::{Path, PathOwned, U16PathBuf, U8PathBuf};
+use aincradfs_path::trie::PathTrie;
+use alloc::vec::Vec;
+
+pub fn u8_paths() -> Vec<U8PathBuf> {
+    let mut path = U8PathBuf::from("/usr/lib");
+    path.push("libc.so".into());
+    path.set_extension("6".into());
+    path.components()
+        .map(|c| U8PathBuf::from_components([c]))
+        .collect()
+}
+
+pub fn u16_trie() -> usize {
+    let mut trie = PathTrie::new();
+    trie.insert(U16PathBuf::from(r"C:\Windows"), 1);
+    trie.insert(U16PathBuf::from(r"C:\Windows\System32"), 2);
+    trie.count_under_prefix(&U16PathBuf::from(r"C:\Windows"))
+}
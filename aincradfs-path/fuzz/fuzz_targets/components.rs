@@ -0,0 +1,34 @@
+#![no_main]
+
+use aincradfs_path::path::{Path, PathOwned, U16PathBuf, U8PathBuf};
+use libfuzzer_sys::fuzz_target;
+
+fn check<P: PathOwned>(buf: P) {
+    let path = buf.as_path();
+
+    let forward: Vec<_> = path.components().collect();
+    let mut backward: Vec<_> = path.components().rev().collect();
+    backward.reverse();
+    assert!(forward == backward);
+
+    let rebuilt = P::from_components(path.components());
+    assert!(rebuilt.as_path().components().eq(forward.iter().copied()));
+
+    let _ = path.parent();
+    let _ = path.file_name();
+    let _ = path.extension();
+    let _ = path.normalize();
+
+    let mut popped = buf.clone();
+    while popped.pop() {}
+}
+
+fuzz_target!(|data: &[u8]| {
+    check(U8PathBuf::from(data));
+
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    check(U16PathBuf::from(units));
+});
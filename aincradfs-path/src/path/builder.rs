@@ -0,0 +1,59 @@
+use crate::path::{Path, PathOwned};
+
+/// Assembles a path into a single buffer that is allocated up front.
+///
+/// Repeatedly calling [`PathOwned::push`] on a fresh buffer reallocates as the path
+/// grows. When the final length is roughly known, a builder avoids that.
+#[derive(Clone, Debug)]
+pub struct PathBuilder<B: PathOwned> {
+    buf: B,
+}
+
+impl<B: PathOwned> PathBuilder<B> {
+    /// Creates a builder with room for `capacity` code units.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: B::with_capacity(capacity),
+        }
+    }
+
+    /// Appends `component`, with the same rules as [`PathOwned::push`].
+    pub fn push_component(&mut self, component: &<B::Borrowed as Path>::Str) -> &mut Self {
+        self.buf.push(component);
+        self
+    }
+
+    pub fn build(self) -> B {
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{PathBuilder, PathOwned, U16PathBuf, U8PathBuf};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn build_u8() {
+        let names = ["/", "usr", "local", "lib"];
+        let mut builder = PathBuilder::<U8PathBuf>::with_capacity(14);
+        let mut pushed = U8PathBuf::new();
+        for name in names {
+            builder.push_component(BStr::new(name));
+            pushed.push(BStr::new(name));
+        }
+        assert_eq!(builder.build(), pushed);
+    }
+
+    #[test]
+    pub fn build_u16() {
+        let path = PathBuilder::<U16PathBuf>::with_capacity(8)
+            .push_component(u16str!("C:"))
+            .push_component(u16str!(r"\"))
+            .push_component(u16str!("a"))
+            .clone()
+            .build();
+        assert_eq!(path, U16PathBuf::from(r"C:\a"));
+    }
+}
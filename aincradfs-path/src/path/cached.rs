@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::mem;
+use core::ops::{Deref, Range};
+
+use crate::path::{Component, Path, PathOwned, PathStr};
+
+#[derive(Clone, Debug)]
+enum CachedComponent {
+    Prefix(Range<usize>),
+    Root,
+    Current,
+    Parent,
+    Normal(Range<usize>),
+    Empty,
+}
+
+/// An owned path that remembers where its components are.
+///
+/// Comparing two path buffers parses both of them on every `==`. A `CachedPathBuf`
+/// parses itself the first time it is compared, and reuses the component boundaries
+/// until it is modified through [`push`](Self::push), [`pop`](Self::pop) or the
+/// `set_*` methods.
+#[derive(Clone, Debug)]
+pub struct CachedPathBuf<B: PathOwned> {
+    buf: B,
+    components: OnceCell<Vec<CachedComponent>>,
+}
+
+impl<B: PathOwned> CachedPathBuf<B> {
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf,
+            components: OnceCell::new(),
+        }
+    }
+
+    pub fn as_path(&self) -> &B::Borrowed {
+        self.buf.as_path()
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    pub fn push(&mut self, component: &<B::Borrowed as Path>::Str) {
+        self.components.take();
+        self.buf.push(component)
+    }
+
+    pub fn pop(&mut self) -> bool {
+        self.components.take();
+        self.buf.pop()
+    }
+
+    pub fn set_file_name(&mut self, file_name: &<B::Borrowed as Path>::Str) {
+        self.components.take();
+        self.buf.set_file_name(file_name)
+    }
+
+    pub fn set_extension(&mut self, extension: &<B::Borrowed as Path>::Str) -> bool {
+        self.components.take();
+        self.buf.set_extension(extension)
+    }
+
+    fn components(&self) -> &[CachedComponent] {
+        self.components.get_or_init(|| {
+            let base = self.as_path().as_str().as_slice();
+            let range = |part: &<B::Borrowed as Path>::Str| {
+                let start = (part.as_slice().as_ptr() as usize - base.as_ptr() as usize)
+                    / mem::size_of::<<<B::Borrowed as Path>::Str as PathStr>::ComponentType>();
+                start..start + part.len()
+            };
+
+            self.as_path()
+                .components()
+                .map(|component| match component {
+                    Component::Prefix(prefix) => CachedComponent::Prefix(range(prefix)),
+                    Component::Root => CachedComponent::Root,
+                    Component::Current => CachedComponent::Current,
+                    Component::Parent => CachedComponent::Parent,
+                    Component::Normal(name) => CachedComponent::Normal(range(name)),
+                    Component::Empty => CachedComponent::Empty,
+                })
+                .collect()
+        })
+    }
+}
+
+impl<B: PathOwned> From<B> for CachedPathBuf<B> {
+    fn from(value: B) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<B: PathOwned> Deref for CachedPathBuf<B> {
+    type Target = B::Borrowed;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_path()
+    }
+}
+
+impl<B: PathOwned> PartialEq for CachedPathBuf<B> {
+    fn eq(&self, other: &Self) -> bool {
+        let ours = self.as_path().as_str().as_slice();
+        let theirs = other.as_path().as_str().as_slice();
+
+        // fast path for exact match
+        if ours == theirs {
+            return true;
+        }
+
+        let (self_c, other_c) = (self.components(), other.components());
+        self_c.len() == other_c.len()
+            && self_c.iter().zip(other_c).all(|pair| match pair {
+                (CachedComponent::Normal(a), CachedComponent::Normal(b))
+                | (CachedComponent::Prefix(a), CachedComponent::Prefix(b)) => {
+                    ours[a.clone()] == theirs[b.clone()]
+                }
+                (a, b) => mem::discriminant(a) == mem::discriminant(b),
+            })
+    }
+}
+
+impl<B: PathOwned> Eq for CachedPathBuf<B> {}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{CachedPathBuf, U16PathBuf, U8PathBuf};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn cached_eq_u8() {
+        let a = CachedPathBuf::new(U8PathBuf::from("/a//b/./c/"));
+        let b = CachedPathBuf::new(U8PathBuf::from("/a/b\\c"));
+        let c = CachedPathBuf::new(U8PathBuf::from("/a/b/d"));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(b, c);
+    }
+
+    #[test]
+    pub fn cached_eq_u16() {
+        let a = CachedPathBuf::new(U16PathBuf::from("/a//b/./c/"));
+        let b = CachedPathBuf::new(U16PathBuf::from("/a/b\\c"));
+        let c = CachedPathBuf::new(U16PathBuf::from("/a/b/d"));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(b, c);
+    }
+
+    #[test]
+    pub fn cached_invalidate() {
+        let mut a = CachedPathBuf::new(U8PathBuf::from("/a/b"));
+        let b = CachedPathBuf::new(U8PathBuf::from("/a//b/c"));
+        assert_ne!(a, b);
+
+        a.push(BStr::new("c"));
+        assert_eq!(a, b);
+
+        a.set_file_name(BStr::new("d"));
+        assert_ne!(a, b);
+
+        a.pop();
+        a.push(BStr::new("c"));
+        assert_eq!(a, b);
+
+        let mut a = CachedPathBuf::new(U16PathBuf::from("/a/b.txt"));
+        let b = CachedPathBuf::new(U16PathBuf::from("/a/b.log"));
+        assert_ne!(a, b);
+
+        a.set_extension(u16str!("log"));
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,132 @@
+use core::borrow::Borrow;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::path::{Path, PathOwned, PathStr};
+
+/// An owned path that is always in normalized form.
+///
+/// A `CanonPath` can only be made through [`new`](Self::new), which normalizes, and
+/// every operation that changes it normalizes again. Two `CanonPath`s are then equal
+/// exactly when their code units are, so equality and hashing skip parsing
+/// components altogether.
+#[derive(Clone, Debug)]
+pub struct CanonPath<B: PathOwned> {
+    buf: B,
+}
+
+impl<B: PathOwned> CanonPath<B> {
+    pub fn new<P: Path<Owned = B> + ?Sized>(path: &P) -> Self {
+        Self {
+            buf: path.normalize(),
+        }
+    }
+}
+
+impl<B: PathOwned> CanonPath<B>
+where
+    B::Borrowed: Path<Owned = B>,
+{
+    /// Returns the path with `part` pushed onto it, normalized again.
+    pub fn join(&self, part: &<B::Borrowed as Path>::Str) -> Self {
+        let mut buf = self.buf.clone();
+        buf.push(part);
+        Self::new(buf.as_path())
+    }
+}
+
+impl<B: PathOwned> CanonPath<B> {
+    pub fn as_path(&self) -> &B::Borrowed {
+        self.buf.as_path()
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: PathOwned> Deref for CanonPath<B> {
+    type Target = B::Borrowed;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_path()
+    }
+}
+
+impl<B: PathOwned> AsRef<B::Borrowed> for CanonPath<B> {
+    fn as_ref(&self) -> &B::Borrowed {
+        self.as_path()
+    }
+}
+
+impl<B: PathOwned> PartialEq for CanonPath<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_path().as_str().as_slice() == other.as_path().as_str().as_slice()
+    }
+}
+
+impl<B: PathOwned> Eq for CanonPath<B> {}
+
+impl<B: PathOwned> Hash for CanonPath<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Borrow::<[u8]>::borrow(&self.buf).hash(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{CanonPath, Path, PathOwned, U16Path, U8Path, U8PathBuf};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn new() {
+        let path = CanonPath::new(U8Path::from_str(BStr::new(r"/a//b\./c/")));
+        assert_eq!(path.as_str(), "/a/b/c");
+        assert_eq!(path.join(BStr::new("../d/")).as_str(), "/a/b/d");
+        assert_eq!(path.join(BStr::new("/x/./y")).as_str(), "/x/y");
+        assert_eq!(path.into_inner(), U8PathBuf::from("/a/b/c"));
+    }
+
+    #[test]
+    pub fn byte_equality_matches_components() {
+        let paths = [
+            "/a/b",
+            "/a//b/",
+            r"\a\b",
+            "/a/./b",
+            "/a/c/../b",
+            "a/b",
+            "./a/b",
+            "/a/B",
+            "",
+            ".",
+            "..",
+            "/..",
+        ];
+        for a in paths {
+            for b in paths {
+                let (a, b) = (U8PathBuf::from(a), U8PathBuf::from(b));
+                let (ca, cb) = (CanonPath::new(a.as_path()), CanonPath::new(b.as_path()));
+                assert_eq!(ca == cb, ca.components().eq(cb.components()), "{a:?} {b:?}");
+                assert_eq!(ca == cb, a.normalize() == b.normalize());
+            }
+        }
+
+        let a = CanonPath::new(U16Path::from_str(u16str!(r"C:\a\.\b")));
+        let b = CanonPath::new(U16Path::from_str(u16str!("C:/a/b/")));
+        assert_eq!(a, b);
+        assert_eq!(a.as_path().as_str(), u16str!("C:/a/b"));
+    }
+
+    #[test]
+    pub fn hash() {
+        use std::collections::HashSet;
+
+        let set = ["/a/b", "/a//b", r"/a\b/.", "/a/c"]
+            .map(|path| CanonPath::new(U8PathBuf::from(path).as_path()))
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+}
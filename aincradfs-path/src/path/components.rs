@@ -1,7 +1,15 @@
-use std::hash::Hash;
-use std::iter::FusedIterator;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Write;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::Range;
 
-use crate::path::{Path, PathStr};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::path::{Path, PathStr, U16Path, U8Path};
 
 /// Component parsing works by a double-ended state machine; the cursors at the
 /// front and back of the path each keep track of what parts of the path have
@@ -11,19 +19,30 @@ use crate::path::{Path, PathStr};
 /// directory component, and a body (of normal components)
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
 pub(crate) enum State {
-    StartDir = 0, // / or . or nothing
-    Body = 1,     // foo/bar/baz
-    Done = 2,
+    Prefix = 0,   // C:
+    StartDir = 1, // / or . or nothing
+    Body = 2,     // foo/bar/baz
+    Done = 3,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum Component<'a, P: Path + ?Sized> {
+    /// A Windows path prefix, e.g., `C:` or `\\server\share`.
+    ///
+    /// Only paths whose [`Path::prefix_len`] recognizes a prefix produce this
+    /// variant, and it is always the first component.
+    Prefix(&'a P::Str),
+
     /// The root directory component, appears after any prefix and before anything else.
     ///
     /// It represents a separator that designates that a path starts from root.
     Root,
 
     /// A reference to the current directory, i.e., `.`.
+    ///
+    /// This is only produced for a `.` at the start of a relative path, so `.` and
+    /// `./a` keep it but `a/.` does not. Paths compare by their components, which
+    /// makes `.` unequal to the empty path even though both normalize to it.
     Current,
 
     /// A reference to the parent directory, i.e., `..`.
@@ -34,25 +53,114 @@ pub enum Component<'a, P: Path + ?Sized> {
     /// This variant is the most common one, it represents references to files
     /// or directories.
     Normal(&'a P::Str),
+
+    /// An empty component between two separators, as in `a//b`.
+    ///
+    /// This is only produced by [`Components::strict`]; otherwise empty components
+    /// are skipped.
+    Empty,
+}
+impl<'a, P: Path + ?Sized> Component<'a, P> {
+    /// Returns the string form of this component.
+    ///
+    /// The root is returned as [`Path::SEPARATOR`].
+    pub fn as_str(self) -> &'a P::Str {
+        match self {
+            Component::Prefix(prefix) => prefix,
+            Component::Root => P::SEPARATOR,
+            Component::Current => P::CURRENT_DIR,
+            Component::Parent => P::PARENT_DIR,
+            Component::Normal(name) => name,
+            Component::Empty => P::empty().as_str(),
+        }
+    }
+}
+
+impl Component<'_, U8Path> {
+    /// Renders the component for display, with control characters and invalid UTF-8
+    /// written as `\xNN` escapes for each byte.
+    ///
+    /// This keeps names from the guest from doing anything to a terminal they are
+    /// logged to. Other characters, including non-ASCII ones, are left as they are.
+    pub fn display_escaped(&self) -> String {
+        fn escape(escaped: &mut String, bytes: &[u8]) {
+            for byte in bytes {
+                let _ = write!(escaped, "\\x{byte:02x}");
+            }
+        }
+
+        let mut escaped = String::new();
+        for chunk in self.as_str().utf8_chunks() {
+            for c in chunk.valid().chars() {
+                if c.is_control() {
+                    escape(&mut escaped, c.encode_utf8(&mut [0; 4]).as_bytes());
+                } else {
+                    escaped.push(c);
+                }
+            }
+            escape(&mut escaped, chunk.invalid());
+        }
+        escaped
+    }
+}
+
+impl Component<'_, U16Path> {
+    /// Renders the component for display, with control characters and unpaired
+    /// surrogates written as `\u{...}` escapes.
+    ///
+    /// See the `U8Path` version of this method.
+    pub fn display_escaped(&self) -> String {
+        let mut escaped = String::new();
+        for c in char::decode_utf16(self.as_str().as_slice().iter().copied()) {
+            match c {
+                Ok(c) if !c.is_control() => escaped.push(c),
+                Ok(c) => {
+                    let _ = write!(escaped, "\\u{{{:x}}}", u32::from(c));
+                }
+                Err(err) => {
+                    let _ = write!(escaped, "\\u{{{:x}}}", err.unpaired_surrogate());
+                }
+            }
+        }
+        escaped
+    }
 }
+
+impl<'a, P: Path + ?Sized> Clone for Component<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: Path + ?Sized> Copy for Component<'a, P> {}
+
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 pub struct Components<'a, P: Path + ?Sized> {
     // The path left to parse components from
     pub(crate) path: &'a P::Str,
+    pub(crate) prefix_len: usize,
     pub(crate) has_root: bool,
     // The iterator is double-ended, and these two states keep track of what has
     // been produced from either end
     pub(crate) front: State,
     pub(crate) back: State,
+    // Whether `.` and empty components in the body are skipped
+    pub(crate) normalize: bool,
+    // Whether a strict iterator has consumed the whole body but one empty component,
+    // which the empty slice left cannot show
+    pub(crate) pending_empty: bool,
 }
 
 impl<'a, P: Path + ?Sized> Clone for Components<'a, P> {
     fn clone(&self) -> Self {
         Self {
             path: self.path,
+            prefix_len: self.prefix_len,
             has_root: self.has_root,
-            front: self.front.clone(),
-            back: self.back.clone(),
+            front: self.front,
+            back: self.back,
+            normalize: self.normalize,
+            pending_empty: self.pending_empty,
         }
     }
 }
@@ -65,7 +173,7 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
         // the beginning of a path, which is treated
         // separately via `include_cur_dir`
         if comp == P::CURRENT_DIR {
-            return None;
+            return (!self.normalize).then_some(Component::Current);
         };
 
         if comp == P::PARENT_DIR {
@@ -73,7 +181,7 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
         };
 
         if comp.is_empty() {
-            return None;
+            return (!self.normalize).then_some(Component::Empty);
         }
 
         Some(Component::Normal(comp))
@@ -84,6 +192,16 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
         self.front == State::Done || self.back == State::Done || self.front > self.back
     }
 
+    // How much of the prefix is left to consume from the front?
+    #[inline]
+    fn prefix_remaining(&self) -> usize {
+        if self.front == State::Prefix {
+            self.prefix_len
+        } else {
+            0
+        }
+    }
+
     // Given the iteration so far, how much of the pre-State::Body path is left?
     #[inline]
     fn len_before_body(&self) -> usize {
@@ -93,25 +211,20 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
             0
         };
         let cur_dir = if self.front <= State::StartDir && self.include_cur_dir() {
-            1
+            self.cur_dir_len()
         } else {
             0
         };
-        root + cur_dir
+        self.prefix_remaining() + root + cur_dir
     }
 
     // parse a component from the left, saying how many bytes to consume to
     // remove the component
     fn parse_next_component(&self) -> (usize, Option<Component<'a, P>>) {
         debug_assert!(self.front == State::Body);
-        let (extra, comp) = match self
-            .path
-            .as_slice()
-            .iter()
-            .position(|b| P::is_separator(*b))
-        {
+        let (extra, comp) = match P::find_separator(self.path) {
             None => (0, self.path),
-            Some(i) => (1, P::Str::from_slice(&self.path.as_slice()[..i])),
+            Some(i) => (1, self.path.split_at(i).0),
         };
         (comp.len() + extra, self.parse_single_component(comp))
     }
@@ -121,16 +234,14 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
     fn parse_next_component_back(&self) -> (usize, Option<Component<'a, P>>) {
         debug_assert!(self.back == State::Body);
         let start = self.len_before_body();
-        let (extra, comp) = match self.path.as_slice()[start..]
-            .iter()
-            .rposition(|b| P::is_separator(*b))
-        {
-            None => (0, P::Str::from_slice(&self.path.as_slice()[start..])),
-            Some(i) => (
-                1,
-                P::Str::from_slice(&self.path.as_slice()[start + i + 1..]),
-            ),
-        };
+        let (extra, comp) =
+            match P::rfind_separator(P::Str::from_slice(&self.path.as_slice()[start..])) {
+                None => (0, P::Str::from_slice(&self.path.as_slice()[start..])),
+                Some(i) => (
+                    1,
+                    P::Str::from_slice(&self.path.as_slice()[start + i + 1..]),
+                ),
+            };
         (comp.len() + extra, self.parse_single_component(comp))
     }
 
@@ -141,7 +252,7 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
             if comp.is_some() {
                 return;
             } else {
-                self.path = P::Str::from_slice(&self.path.as_slice()[size..]);
+                self.path = self.path.split_at(size).1;
             }
         }
     }
@@ -153,17 +264,27 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
             if comp.is_some() {
                 return;
             } else {
-                self.path = P::Str::from_slice(&self.path.as_slice()[..self.path.len() - size]);
+                self.path = self.path.split_at(self.path.len() - size).0;
             }
         }
     }
 
+    // How much a leading `.` takes up. A strict iterator takes the separator after it
+    // too, so that it is not read as an empty component.
+    fn cur_dir_len(&self) -> usize {
+        if !self.normalize && self.path.len() > self.prefix_remaining() + 1 {
+            2
+        } else {
+            1
+        }
+    }
+
     /// Should the normalized path include a leading . ?
     fn include_cur_dir(&self) -> bool {
         if self.has_root {
             return false;
         }
-        let mut iter = self.path.as_slice().iter();
+        let mut iter = self.path.as_slice()[self.prefix_remaining()..].iter();
         let current_dir = &P::Str::as_slice(P::CURRENT_DIR)[0];
 
         match (iter.next(), iter.next()) {
@@ -183,6 +304,81 @@ impl<'a, P: Path + ?Sized> Components<'a, P> {
         }
         P::from_str(comps.path)
     }
+
+    /// Returns the part of the path that has not been consumed yet, verbatim.
+    ///
+    /// Unlike [`as_path`](Self::as_path), repeated separators and `.` around the
+    /// remaining components are not trimmed, so after taking `a` from `a//./b` this
+    /// returns `/./b` rather than `b`.
+    pub fn rest(&self) -> &'a P::Str {
+        self.path
+    }
+
+    /// Returns the next component without consuming it.
+    ///
+    /// The iterator only holds a slice and a little state, so this works on a copy of
+    /// it and can be mixed freely with [`next_back`](DoubleEndedIterator::next_back).
+    pub fn peek(&self) -> Option<Component<'a, P>> {
+        self.clone().next()
+    }
+
+    /// Returns the next component from the back without consuming it.
+    pub fn peek_back(&self) -> Option<Component<'a, P>> {
+        self.clone().next_back()
+    }
+
+    /// Keeps `.` and empty components in the middle of the path, as
+    /// [`Current`](Component::Current) and [`Empty`](Component::Empty), rather than
+    /// skipping them.
+    ///
+    /// Strict components compare equal only if the paths are spelled the same, apart
+    /// from which separators they use and a single trailing one, so `/a/./b` and
+    /// `/a//b` are both unequal to `/a/b`. This should be called before iterating.
+    pub fn strict(mut self) -> Self {
+        if self.normalize {
+            self.normalize = false;
+            // a trailing separator is still ignored, as it is by `components`
+            let slice = self.path.as_slice();
+            if self.back == State::Body
+                && slice.len() > self.len_before_body()
+                && slice.last().is_some_and(|c| P::is_separator(*c))
+            {
+                self.path = P::Str::from_slice(&slice[..slice.len() - 1]);
+                // as in `//`, whose body is now one empty component
+                self.pending_empty = self.path.len() == self.len_before_body();
+            }
+        }
+        self
+    }
+
+    /// Skips any prefix, root or leading `.` that has not been produced yet, so that
+    /// the next component from the front is a [`Normal`](Component::Normal) or
+    /// [`Parent`](Component::Parent) one.
+    pub fn skip_prefix(mut self) -> Self {
+        if self.front < State::Body {
+            if self.back == State::Body {
+                self.path = self.path.split_at(self.len_before_body()).1;
+            }
+            self.front = State::Body;
+        }
+        self
+    }
+}
+
+impl<'a, P: Path + ?Sized> Components<'a, P> {
+    /// Lexically normalizes the remaining components as they are iterated.
+    ///
+    /// This yields the same components as [`Path::normalize`] without building a new
+    /// path. A normal component may still be cancelled by any later `..`, so normal
+    /// components are held back until the underlying iterator is exhausted.
+    pub fn normalize(self) -> Normalize<'a, P> {
+        Normalize {
+            inner: self,
+            pending: Vec::new(),
+            rooted: false,
+            drained: false,
+        }
+    }
 }
 
 impl<'a, P: Path + ?Sized> Iterator for Components<'a, P> {
@@ -191,25 +387,47 @@ impl<'a, P: Path + ?Sized> Iterator for Components<'a, P> {
     fn next(&mut self) -> Option<Component<'a, P>> {
         while !self.finished() {
             match self.front {
+                State::Prefix if self.prefix_len > 0 => {
+                    self.front = State::StartDir;
+                    debug_assert!(self.prefix_len <= self.path.len());
+                    let (prefix, rest) = self.path.split_at(self.prefix_len);
+                    self.path = rest;
+                    return Some(Component::Prefix(prefix));
+                }
+                State::Prefix => {
+                    self.front = State::StartDir;
+                }
                 State::StartDir => {
                     self.front = State::Body;
                     if self.has_root {
                         debug_assert!(!self.path.is_empty());
-                        self.path = P::Str::from_slice(&self.path.as_slice()[1..]);
+                        self.path = self.path.split_at(1).1;
                         return Some(Component::Root);
                     } else if self.include_cur_dir() {
                         debug_assert!(!self.path.is_empty());
-                        self.path = P::Str::from_slice(&self.path.as_slice()[1..]);
+                        self.path = self.path.split_at(self.cur_dir_len()).1;
                         return Some(Component::Current);
                     }
                 }
                 State::Body if !self.path.is_empty() => {
                     let (size, comp) = self.parse_next_component();
-                    self.path = P::Str::from_slice(&self.path.as_slice()[size..]);
+                    let (consumed, rest) = self.path.split_at(size);
+                    self.path = rest;
+                    if !self.normalize && rest.is_empty() {
+                        // `a/` still has an empty component after the separator
+                        self.pending_empty = consumed
+                            .as_slice()
+                            .last()
+                            .is_some_and(|c| P::is_separator(*c));
+                    }
                     if comp.is_some() {
                         return comp;
                     }
                 }
+                State::Body if self.pending_empty => {
+                    self.pending_empty = false;
+                    return Some(Component::Empty);
+                }
                 State::Body => {
                     self.front = State::Done;
                 }
@@ -226,26 +444,45 @@ impl<'a, P: Path + ?Sized> DoubleEndedIterator for Components<'a, P> {
             match self.back {
                 State::Body if self.path.len() > self.len_before_body() => {
                     let (size, comp) = self.parse_next_component_back();
-                    self.path = P::Str::from_slice(&self.path.as_slice()[..self.path.len() - size]);
+                    let (rest, consumed) = self.path.split_at(self.path.len() - size);
+                    self.path = rest;
+                    if !self.normalize && rest.len() == self.len_before_body() {
+                        self.pending_empty = consumed
+                            .as_slice()
+                            .first()
+                            .is_some_and(|c| P::is_separator(*c));
+                    }
                     if comp.is_some() {
                         return comp;
                     }
                 }
+                State::Body if self.pending_empty => {
+                    self.pending_empty = false;
+                    return Some(Component::Empty);
+                }
                 State::Body => {
                     self.back = State::StartDir;
                 }
                 State::StartDir => {
-                    self.back = State::Done;
+                    self.back = State::Prefix;
                     if self.has_root {
-                        self.path =
-                            P::Str::from_slice(&self.path.as_slice()[..self.path.len() - 1]);
+                        self.path = self.path.split_at(self.path.len() - 1).0;
                         return Some(Component::Root);
                     } else if self.include_cur_dir() {
-                        self.path =
-                            P::Str::from_slice(&self.path.as_slice()[..self.path.len() - 1]);
+                        let len = self.cur_dir_len();
+                        self.path = self.path.split_at(self.path.len() - len).0;
                         return Some(Component::Current);
                     }
                 }
+                State::Prefix if self.prefix_len > 0 => {
+                    self.back = State::Done;
+                    let prefix = self.path;
+                    self.path = P::Str::from_slice(&self.path.as_slice()[..0]);
+                    return Some(Component::Prefix(prefix));
+                }
+                State::Prefix => {
+                    self.back = State::Done;
+                }
                 State::Done => unreachable!(),
             }
         }
@@ -255,6 +492,9 @@ impl<'a, P: Path + ?Sized> DoubleEndedIterator for Components<'a, P> {
 
 impl<'a, P: Path + ?Sized> FusedIterator for Components<'a, P> {}
 
+// How many trailing units `Components::eq` compares before the rest of the buffer.
+const TAIL_LEN: usize = 16;
+
 impl<'a, P: Path + ?Sized> PartialEq for Components<'a, P> {
     #[inline]
     fn eq(&self, other: &Components<'a, P>) -> bool {
@@ -262,13 +502,17 @@ impl<'a, P: Path + ?Sized> PartialEq for Components<'a, P> {
         // Don't explicitly compare the prefix or has_physical_root fields since they'll
         // either be covered by the `path` buffer or are only relevant for `prefix_verbatim()`.
         if self.path.len() == other.path.len()
+            && self.normalize == other.normalize
+            && self.pending_empty == other.pending_empty
             && self.front == other.front
             && self.back == State::Body
             && other.back == State::Body
         {
-            // possible future improvement: this could bail out earlier if there were a
-            // reverse memcmp/bcmp comparing back to front
-            if self.path == other.path {
+            // Absolute paths often share long prefixes, so check the tails first to
+            // bail out before comparing the whole buffer.
+            let (ours, theirs) = (self.path.as_slice(), other.path.as_slice());
+            let tail = ours.len().saturating_sub(TAIL_LEN);
+            if ours[tail..] == theirs[tail..] && ours[..tail] == theirs[..tail] {
                 return true;
             }
         }
@@ -281,13 +525,324 @@ impl<'a, P: Path + ?Sized> PartialEq for Components<'a, P> {
     }
 }
 
-impl<'a, P: Path + ?Sized> Eq for Components<'_, P> {}
+impl<P: Path + ?Sized> Eq for Components<'_, P> {}
+
+impl<'a, P: Path + ?Sized> PartialOrd for Components<'a, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders component by component, in the order [`Component`]'s variants are declared,
+/// with prefixes and normal names compared code unit by code unit.
+impl<'a, P: Path + ?Sized> Ord for Components<'a, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |component: Component<'a, P>| {
+            let rank = match component {
+                Component::Prefix(_) => 0,
+                Component::Root => 1,
+                Component::Current => 2,
+                Component::Parent => 3,
+                Component::Normal(_) => 4,
+                Component::Empty => 5,
+            };
+            let name = match component {
+                Component::Prefix(name) | Component::Normal(name) => name.as_slice(),
+                _ => &[],
+            };
+            (rank, name)
+        };
+        self.clone().map(key).cmp(other.clone().map(key))
+    }
+}
+
+// Hashes a path by its components, so that paths which compare equal hash equally.
+pub(crate) fn hash_path<P: Path + ?Sized, H: Hasher>(path: &P, state: &mut H)
+where
+    P::Str: Hash,
+{
+    for component in path.components() {
+        mem::discriminant(&component).hash(state);
+        match component {
+            Component::Prefix(part) | Component::Normal(part) => part.hash(state),
+            Component::Root | Component::Current | Component::Parent | Component::Empty => {}
+        }
+    }
+}
+
+// Formats a path as its raw string followed by the components it parses into,
+// with normal components shown as bare strings.
+pub(crate) fn debug_path<P: Path + ?Sized>(
+    name: &str,
+    path: &P,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result
+where
+    P::Str: fmt::Debug,
+{
+    struct DebugComponent<'a, P: Path + ?Sized>(Component<'a, P>);
+
+    impl<P: Path + ?Sized> fmt::Debug for DebugComponent<'_, P>
+    where
+        P::Str: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0 {
+                Component::Prefix(prefix) => f.debug_tuple("Prefix").field(&prefix).finish(),
+                Component::Root => f.write_str("Root"),
+                Component::Current => f.write_str("Current"),
+                Component::Parent => f.write_str("Parent"),
+                Component::Normal(name) => fmt::Debug::fmt(name, f),
+                Component::Empty => f.write_str("Empty"),
+            }
+        }
+    }
+
+    struct DebugComponents<'a, P: Path + ?Sized>(&'a P);
+
+    impl<P: Path + ?Sized> fmt::Debug for DebugComponents<'_, P>
+    where
+        P::Str: fmt::Debug,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_list()
+                .entries(self.0.components().map(DebugComponent))
+                .finish()
+        }
+    }
+
+    f.debug_struct(name)
+        .field("path", &path.as_str())
+        .field("components", &DebugComponents(path))
+        .finish()
+}
+
+/// An iterator that lexically normalizes components.
+///
+/// Only a prefix, a root and leading `..` are yielded as they are read. Any later
+/// `..` may cancel a normal component, so every normal component is held in a
+/// buffer until the underlying iterator is exhausted. That buffer grows with the
+/// depth of the path, so this is no lazier than [`Path::normalize`]. It only
+/// avoids building a new path.
+///
+/// This struct is created by [`Components::normalize`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Normalize<'a, P: Path + ?Sized> {
+    inner: Components<'a, P>,
+    // normal components that a later `..` could still cancel, reversed once drained
+    pending: Vec<Component<'a, P>>,
+    rooted: bool,
+    drained: bool,
+}
+
+impl<'a, P: Path + ?Sized> Iterator for Normalize<'a, P> {
+    type Item = Component<'a, P>;
+
+    fn next(&mut self) -> Option<Component<'a, P>> {
+        while !self.drained {
+            match self.inner.next() {
+                Some(Component::Root) => {
+                    self.rooted = true;
+                    return Some(Component::Root);
+                }
+                Some(Component::Current | Component::Empty) => {}
+                Some(component @ Component::Normal(_)) => self.pending.push(component),
+                Some(Component::Parent) => {
+                    // with nothing left to cancel, a relative path keeps its `..`
+                    if self.pending.pop().is_none() && !self.rooted {
+                        return Some(Component::Parent);
+                    }
+                }
+                Some(component @ Component::Prefix(_)) => return Some(component),
+                None => {
+                    self.drained = true;
+                    self.pending.reverse();
+                }
+            }
+        }
+        self.pending.pop()
+    }
+}
+
+impl<'a, P: Path + ?Sized> FusedIterator for Normalize<'a, P> {}
+
+/// An iterator over the components of a path as strings.
+///
+/// This struct is created by [`Path::iter`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Iter<'a, P: Path + ?Sized> {
+    pub(crate) inner: Components<'a, P>,
+}
+
+impl<'a, P: Path + ?Sized> Clone for Iter<'a, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> Iterator for Iter<'a, P> {
+    type Item = &'a P::Str;
+
+    fn next(&mut self) -> Option<&'a P::Str> {
+        self.inner.next().map(Component::as_str)
+    }
+}
+
+impl<'a, P: Path + ?Sized> DoubleEndedIterator for Iter<'a, P> {
+    fn next_back(&mut self) -> Option<&'a P::Str> {
+        self.inner.next_back().map(Component::as_str)
+    }
+}
+
+impl<'a, P: Path + ?Sized> FusedIterator for Iter<'a, P> {}
+
+/// An iterator over the components of a path along with where each one lies in it.
+///
+/// Ranges are in code units of the original path, so they can be used to slice
+/// [`Path::as_str`]. The range of a root is the separator that makes the path rooted.
+///
+/// This struct is created by [`Path::components_indexed`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ComponentsIndexed<'a, P: Path + ?Sized> {
+    pub(crate) inner: Components<'a, P>,
+    pub(crate) base: &'a P::Str,
+}
+
+impl<'a, P: Path + ?Sized> ComponentsIndexed<'a, P> {
+    // Offset of `part` within the original path, which it borrows from.
+    fn offset(&self, part: &P::Str) -> usize {
+        (part.as_slice().as_ptr() as usize - self.base.as_slice().as_ptr() as usize)
+            / mem::size_of::<<P::Str as PathStr>::ComponentType>()
+    }
+
+    fn len(component: &Component<'a, P>) -> usize {
+        match component {
+            Component::Prefix(part) | Component::Normal(part) => part.len(),
+            Component::Root => 1,
+            Component::Current => P::CURRENT_DIR.len(),
+            Component::Parent => P::PARENT_DIR.len(),
+            Component::Empty => 0,
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> Clone for ComponentsIndexed<'a, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            base: self.base,
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> Iterator for ComponentsIndexed<'a, P> {
+    type Item = (Component<'a, P>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // trimming leaves the remainder starting at the next component
+        let start = self.offset(self.inner.as_path().as_str());
+        let component = self.inner.next()?;
+        Some((component, start..start + Self::len(&component)))
+    }
+}
+
+impl<'a, P: Path + ?Sized> DoubleEndedIterator for ComponentsIndexed<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // and ending at the end of the last one
+        let rest = self.inner.as_path().as_str();
+        let end = self.offset(rest) + rest.len();
+        let component = self.inner.next_back()?;
+        Some((component, end - Self::len(&component)..end))
+    }
+}
+
+impl<'a, P: Path + ?Sized> FusedIterator for ComponentsIndexed<'a, P> {}
+
+/// An iterator over every separator-delimited segment of a path, verbatim.
+///
+/// Unlike [`Components`], empty segments and `.` are yielded as they appear, so
+/// `a//b/.` yields `a`, ``, `b` and `.`. A leading separator yields an empty
+/// segment first, and an empty path yields nothing.
+///
+/// This struct is created by [`Path::raw_components`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct RawComponents<'a, P: Path + ?Sized> {
+    // The segments left to yield
+    path: &'a P::Str,
+    finished: bool,
+}
+
+impl<'a, P: Path + ?Sized> RawComponents<'a, P> {
+    pub(crate) fn new(path: &'a P::Str) -> Self {
+        Self {
+            path,
+            finished: path.is_empty(),
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> Clone for RawComponents<'a, P> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path,
+            finished: self.finished,
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> Iterator for RawComponents<'a, P> {
+    type Item = &'a P::Str;
+
+    fn next(&mut self) -> Option<&'a P::Str> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.path.as_slice();
+        match P::find_separator(self.path) {
+            Some(i) => {
+                self.path = P::Str::from_slice(&slice[i + 1..]);
+                Some(P::Str::from_slice(&slice[..i]))
+            }
+            None => {
+                self.finished = true;
+                Some(self.path)
+            }
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> DoubleEndedIterator for RawComponents<'a, P> {
+    fn next_back(&mut self) -> Option<&'a P::Str> {
+        if self.finished {
+            return None;
+        }
+
+        let slice = self.path.as_slice();
+        match P::rfind_separator(self.path) {
+            Some(i) => {
+                self.path = P::Str::from_slice(&slice[..i]);
+                Some(P::Str::from_slice(&slice[i + 1..]))
+            }
+            None => {
+                self.finished = true;
+                Some(self.path)
+            }
+        }
+    }
+}
+
+impl<'a, P: Path + ?Sized> FusedIterator for RawComponents<'a, P> {}
 
 #[cfg(test)]
 mod test {
     use crate::path::u16path::U16PathBuf;
     use crate::path::u8path::U8PathBuf;
-    use crate::path::Path;
+    use crate::path::{Component, Path, PathOwned, U16Path, U8Path};
+    use bstr::ByteSlice;
+    use widestring::{u16str, U16Str};
     //
     #[test]
     pub fn test_wstr() {
@@ -322,4 +877,507 @@ mod test {
 
         assert_eq!(path, path2)
     }
+
+    fn assert_components(path: &U16PathBuf, expected: &[Component<U16Path>]) {
+        assert_eq!(path.components().collect::<Vec<_>>(), expected);
+        assert!(path.components().rev().eq(expected.iter().copied().rev()));
+    }
+
+    #[test]
+    pub fn test_prefix_disk() {
+        let path = U16PathBuf::from(r"C:\a\b");
+        assert_components(
+            &path,
+            &[
+                Component::Prefix(u16str!("C:")),
+                Component::Root,
+                Component::Normal(u16str!("a")),
+                Component::Normal(u16str!("b")),
+            ],
+        );
+
+        let path = U16PathBuf::from(r"C:a");
+        assert_components(
+            &path,
+            &[
+                Component::Prefix(u16str!("C:")),
+                Component::Normal(u16str!("a")),
+            ],
+        );
+    }
+
+    #[test]
+    pub fn test_prefix_verbatim() {
+        let path = U16PathBuf::from(r"\\?\C:\a");
+        assert_components(
+            &path,
+            &[
+                Component::Prefix(u16str!(r"\\?\C:")),
+                Component::Root,
+                Component::Normal(u16str!("a")),
+            ],
+        );
+    }
+
+    #[test]
+    pub fn test_prefix_unc() {
+        let path = U16PathBuf::from(r"\\server\share\a");
+        assert_components(
+            &path,
+            &[
+                Component::Prefix(u16str!(r"\\server\share")),
+                Component::Root,
+                Component::Normal(u16str!("a")),
+            ],
+        );
+    }
+
+    #[test]
+    pub fn test_prefix_u8() {
+        let path = U8PathBuf::from(r"C:\a");
+        assert_eq!(path.components().count(), 2);
+    }
+
+    #[test]
+    pub fn test_many_separators() {
+        // U+2F2F and U+5C5C are made of separator bytes, but are not separators.
+        let segment = "//a\\\\⼯b//./..//屜c\\";
+        let input = segment.repeat(64);
+
+        let expected = input
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != ".")
+            .collect::<Vec<_>>();
+
+        let path = U8PathBuf::from(input.as_str());
+        let components = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(c) => Some(c.to_str().unwrap()),
+                Component::Parent => Some(".."),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected, components);
+
+        let path = U16PathBuf::from(input.as_str());
+        let components = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(c) => Some(c.to_string().unwrap()),
+                Component::Parent => Some("..".to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected, components);
+
+        let forward = path.components().collect::<Vec<_>>();
+        let mut backward = path.components().rev().collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    pub fn test_raw_components() {
+        let path = U8Path::from_str("a//b/.".into());
+        let expected = ["a", "", "b", "."];
+        let raw = path.raw_components().map(|c| c.to_str().unwrap());
+        assert!(raw.clone().eq(expected));
+        assert!(raw.rev().eq(expected.into_iter().rev()));
+
+        let path = U16Path::from_str(u16str!(r"/a\\b/"));
+        let expected = [
+            u16str!(""),
+            u16str!("a"),
+            u16str!(""),
+            u16str!("b"),
+            u16str!(""),
+        ];
+        assert!(path.raw_components().eq(expected));
+
+        assert_eq!(U8Path::empty().raw_components().count(), 0);
+    }
+
+    #[test]
+    pub fn test_iter() {
+        let path = U8PathBuf::from("/a/b");
+        let iter = path.iter().map(|c| c.to_str().unwrap());
+        assert_eq!(iter.collect::<Vec<_>>(), ["/", "a", "b"]);
+
+        let path = U16PathBuf::from(r"C:\a\..\.");
+        let expected = [u16str!("C:"), u16str!("/"), u16str!("a"), u16str!("..")];
+        assert!(path.iter().eq(expected));
+        assert!(path.iter().rev().eq(expected.into_iter().rev()));
+    }
+
+    #[test]
+    pub fn test_as_path_parent() {
+        let path = U8PathBuf::from("a/../b");
+        let mut comps = path.components();
+        assert_eq!(comps.next(), Some(Component::Normal("a".into())));
+        assert_eq!(comps.as_path().as_str(), "../b");
+        assert_eq!(comps.next_back(), Some(Component::Normal("b".into())));
+        assert_eq!(comps.as_path().as_str(), "..");
+        assert_eq!(comps.next(), Some(Component::Parent));
+        assert_eq!(comps.as_path().as_str(), "");
+
+        let path = U16PathBuf::from(r"C:\a\..\..");
+        let mut comps = path.components();
+        assert_eq!(comps.next_back(), Some(Component::Parent));
+        assert_eq!(comps.as_path().as_str(), u16str!(r"C:\a\.."));
+        comps.next();
+        comps.next();
+        assert_eq!(comps.as_path().as_str(), u16str!(r"a\.."));
+    }
+
+    #[test]
+    pub fn test_normalize() {
+        let cases = [
+            "/a/./b/../c",
+            "../a/../../b",
+            "a/b/c/../../..",
+            "./a",
+            "/../a",
+            "a/..",
+            "",
+        ];
+        for case in cases {
+            let path = U8PathBuf::from(case);
+            let normal = path.normalize();
+            assert!(
+                path.components().normalize().eq(normal.components()),
+                "{case}"
+            );
+        }
+
+        let path = U16PathBuf::from(r"C:..\a\..\b");
+        let normal = path.normalize();
+        assert!(path.components().normalize().eq(normal.components()));
+        assert_eq!(
+            path.components().normalize().collect::<Vec<_>>(),
+            [
+                Component::Prefix(u16str!("C:")),
+                Component::Parent,
+                Component::Normal(u16str!("b")),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_eq_near() {
+        let base = "/mnt/some/deeply/nested/directory/structure";
+        let a = U16PathBuf::from(format!("{base}/file.txt").as_str());
+        let b = U16PathBuf::from(format!("{base}/file.txs").as_str());
+        let c = U16PathBuf::from("/Mnt/some/deeply/nested/directory/structure/file.txt");
+        let d = U16PathBuf::from(format!("{base}\\file.txt").as_str());
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a, d);
+        assert!(a.components() == d.components());
+        assert!(a.components() != c.components());
+
+        let a = U8PathBuf::from("/a/b");
+        let b = U8PathBuf::from("/a/c");
+        assert!(a.components() != b.components());
+        assert!(a.components() == a.clone().components());
+    }
+
+    // Every way of alternating next() and next_back() over `path` must hand out the
+    // same components as plain forward iteration, front ones in order and back ones
+    // in reverse.
+    fn assert_interleaved<P: Path + core::fmt::Debug + ?Sized>(path: &P) {
+        let forward = path.components().collect::<Vec<_>>();
+        for mask in 0u32..1 << (forward.len() + 1) {
+            let mut comps = path.components();
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            for step in 0.. {
+                let next = if mask >> step & 1 == 0 {
+                    comps.next().map(|c| front.push(c))
+                } else {
+                    comps.next_back().map(|c| back.push(c))
+                };
+                if next.is_none() {
+                    break;
+                }
+            }
+            assert!(comps.next().is_none() && comps.next_back().is_none());
+
+            front.extend(back.into_iter().rev());
+            assert!(front == forward, "mask {mask:#b} on {path:?}");
+        }
+    }
+
+    #[test]
+    pub fn test_interleaved() {
+        let paths = [
+            "./a/b/../",
+            "./a/b/..",
+            ".",
+            "./",
+            ".//a/./",
+            "/",
+            "//a//",
+            "/./a/../b/./",
+            "a",
+            "../..",
+            "a/.",
+            "./.",
+            "",
+        ];
+        for path in paths {
+            assert_interleaved(U8Path::from_str(path.as_bytes().as_bstr()));
+            assert_interleaved(U16PathBuf::from(path).as_path());
+        }
+
+        for path in [
+            r"C:\a\.\b\..\",
+            r"C:.\a",
+            r"C:",
+            r"C:\",
+            r"\\server\share\a\.",
+            r"\\?\C:\a\..\b",
+        ] {
+            assert_interleaved(U16PathBuf::from(path).as_path());
+        }
+    }
+
+    #[test]
+    pub fn test_peek() {
+        let path = U8PathBuf::from("./a//b/..");
+        let mut comps = path.components();
+        assert_eq!(comps.peek(), Some(Component::Current));
+        assert_eq!(comps.next(), Some(Component::Current));
+        assert_eq!(comps.peek(), Some(Component::Normal(b"a".as_bstr())));
+        assert_eq!(comps.peek_back(), Some(Component::Parent));
+        assert_eq!(comps.next_back(), Some(Component::Parent));
+        assert_eq!(comps.peek_back(), Some(Component::Normal(b"b".as_bstr())));
+        assert_eq!(comps.next(), Some(Component::Normal(b"a".as_bstr())));
+        assert_eq!(comps.peek(), comps.peek_back());
+        assert_eq!(comps.next(), Some(Component::Normal(b"b".as_bstr())));
+        assert_eq!(comps.peek(), None);
+        assert_eq!(comps.peek_back(), None);
+    }
+
+    #[test]
+    pub fn test_components_indexed() {
+        let path = U8PathBuf::from("/a/bb//./ccc/");
+        let indexed = path.components_indexed().collect::<Vec<_>>();
+        assert_eq!(
+            indexed,
+            [
+                (Component::Root, 0..1),
+                (Component::Normal(b"a".as_bstr()), 1..2),
+                (Component::Normal(b"bb".as_bstr()), 3..5),
+                (Component::Normal(b"ccc".as_bstr()), 9..12),
+            ]
+        );
+        for (component, range) in &indexed {
+            assert_eq!(component.as_str(), &path.as_str()[range.clone()]);
+        }
+        assert!(path
+            .components_indexed()
+            .rev()
+            .eq(indexed.into_iter().rev()));
+
+        let path = U16PathBuf::from(r"C:.\a\..");
+        let ranges = path
+            .components_indexed()
+            .map(|(_, range)| range)
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [0..2, 2..3, 4..5, 6..8]);
+        assert!(path
+            .components_indexed()
+            .rev()
+            .map(|(_, range)| range)
+            .eq(ranges.into_iter().rev()));
+    }
+
+    #[test]
+    pub fn test_degenerate() {
+        let cases: [(&str, &[Component<U8Path>]); 10] = [
+            ("", &[]),
+            ("/", &[Component::Root]),
+            ("///", &[Component::Root]),
+            (r"/\/.//", &[Component::Root]),
+            (".", &[Component::Current]),
+            ("./", &[Component::Current]),
+            ("././/", &[Component::Current]),
+            ("..", &[Component::Parent]),
+            ("../", &[Component::Parent]),
+            ("/..", &[Component::Root, Component::Parent]),
+        ];
+        for (path, expected) in cases {
+            let path = U8PathBuf::from(path);
+            assert_eq!(path.components().collect::<Vec<_>>(), expected);
+            assert!(path.components().rev().eq(expected.iter().copied().rev()));
+
+            let path = U16PathBuf::from(path.to_str().unwrap());
+            assert_eq!(path.components().count(), expected.len());
+            assert!(path.components().rev().eq(path
+                .components()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()));
+        }
+    }
+
+    #[test]
+    pub fn test_rest() {
+        let path = U8PathBuf::from("/a//./b/c/");
+        let mut comps = path.components();
+        assert_eq!(comps.rest(), "/a//./b/c/");
+        comps.next();
+        assert_eq!(comps.rest(), "a//./b/c/");
+        comps.next();
+        assert_eq!(comps.rest(), "/./b/c/");
+        assert_eq!(comps.as_path().as_str(), "b/c");
+        comps.next_back();
+        assert_eq!(comps.rest(), "/./b");
+        assert_eq!(comps.as_path().as_str(), "b");
+
+        let path = U16PathBuf::from(r"C:\a\b");
+        let mut comps = path.components();
+        comps.next();
+        assert_eq!(comps.rest(), u16str!(r"\a\b"));
+    }
+
+    #[test]
+    pub fn skip_prefix() {
+        let path = U8PathBuf::from("/a/b");
+        let mut iter = path.components().skip_prefix();
+        assert_eq!(
+            iter.next(),
+            Some(Component::Normal("a".as_bytes().as_bstr()))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(Component::Normal("b".as_bytes().as_bstr()))
+        );
+        assert_eq!(iter.next(), None);
+
+        let path = U8PathBuf::from("./../a");
+        let mut iter = path.components().skip_prefix();
+        assert_eq!(iter.next(), Some(Component::Parent));
+        assert_eq!(iter.as_path().as_str(), "a");
+
+        let path = U16PathBuf::from(r"C:\a");
+        let iter = path.components().skip_prefix();
+        assert!(iter.eq([Component::Normal(u16str!("a"))]));
+
+        // nothing is left once the back has consumed the body
+        let path = U8PathBuf::from("/a");
+        let mut iter = path.components();
+        assert_eq!(
+            iter.next_back(),
+            Some(Component::Normal("a".as_bytes().as_bstr()))
+        );
+        assert_eq!(iter.skip_prefix().next(), None);
+
+        let path = U8PathBuf::from("/");
+        assert_eq!(path.components().skip_prefix().next(), None);
+    }
+
+    #[test]
+    pub fn current_dir() {
+        let path = U8PathBuf::from(".");
+        assert!(path.components().eq([Component::Current]));
+        assert!(path.components().rev().eq([Component::Current]));
+        assert_eq!(U8PathBuf::from_components(path.components()).as_str(), ".");
+        assert_eq!(path.components().as_path().as_str(), ".");
+        assert_eq!(U8PathBuf::from("./"), path);
+        assert_eq!(U8PathBuf::from(".//."), path);
+
+        let path = U8PathBuf::from("./a");
+        let a = Component::Normal("a".as_bytes().as_bstr());
+        assert!(path.components().eq([Component::Current, a]));
+        assert!(path.components().rev().eq([a, Component::Current]));
+        assert_eq!(
+            U8PathBuf::from_components(path.components()).as_str(),
+            "./a"
+        );
+        assert_eq!(U8PathBuf::from("a/."), U8PathBuf::from("a"));
+
+        // `.` names the current directory, the empty path names nothing
+        assert_ne!(U8PathBuf::from("."), U8PathBuf::new());
+        assert_ne!(U16PathBuf::from("."), U16PathBuf::new());
+        assert_eq!(U8PathBuf::from(".").normalize(), U8PathBuf::new());
+
+        let path = U16PathBuf::from("C:.");
+        assert!(path
+            .components()
+            .eq([Component::Prefix(u16str!("C:")), Component::Current]));
+        assert!(path
+            .components()
+            .rev()
+            .eq([Component::Current, Component::Prefix(u16str!("C:"))]));
+    }
+
+    #[test]
+    pub fn display_escaped() {
+        let name = Component::<U8Path>::Normal(b"bad\nname\0\xff\x1b[2J".as_bstr());
+        assert_eq!(name.display_escaped(), r"bad\x0aname\x00\xff\x1b[2J");
+        let name = Component::<U8Path>::Normal("naïve 😀".as_bytes().as_bstr());
+        assert_eq!(name.display_escaped(), "naïve 😀");
+        assert_eq!(Component::<U8Path>::Parent.display_escaped(), "..");
+
+        let units = [b'a' as u16, b'\n' as u16, 0, 0xD800, 0xE9];
+        let name = Component::<U16Path>::Normal(U16Str::from_slice(&units));
+        assert_eq!(name.display_escaped(), r"a\u{a}\u{0}\u{d800}é");
+        assert_eq!(Component::<U16Path>::Root.display_escaped(), "/");
+    }
+
+    #[test]
+    pub fn strict() {
+        let (dotted, plain) = (
+            U8Path::from_str(b"/a/./b".as_bstr()),
+            U8Path::from_str(b"/a/b".as_bstr()),
+        );
+        assert!(dotted.components() == plain.components());
+        assert!(dotted.components().strict() != plain.components().strict());
+        assert!(plain.components().strict() == plain.components().strict());
+        assert!(dotted.components().strict() != dotted.components());
+        assert!(dotted.components().strict().eq([
+            Component::Root,
+            Component::Normal(b"a".as_bstr()),
+            Component::Current,
+            Component::Normal(b"b".as_bstr()),
+        ]));
+
+        let path = U8Path::from_str(b"/a//b/".as_bstr());
+        assert!(path.components().strict().eq([
+            Component::Root,
+            Component::Normal(b"a".as_bstr()),
+            Component::Empty,
+            Component::Normal(b"b".as_bstr()),
+        ]));
+        assert!(
+            path.components().strict()
+                == U8Path::from_str(b"/a//b".as_bstr()).components().strict()
+        );
+
+        let mut components = U8Path::from_str(b"a//".as_bstr()).components().strict();
+        assert_eq!(components.next(), Some(Component::Normal(b"a".as_bstr())));
+        assert_eq!(components.next_back(), Some(Component::Empty));
+        assert_eq!(components.next(), None);
+
+        for path in [
+            "", "/", "//", "///", "////", "./.", ".//a", "a/./", "a//b", "/./", "../..",
+        ] {
+            let path = U8PathBuf::from(path);
+            let forward = path.components().strict().collect::<Vec<_>>();
+            let mut backward = path.components().strict().rev().collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward, "{path:?}");
+        }
+
+        let path = U16Path::from_str(u16str!(r"C:\a\.\b"));
+        assert!(path.components().strict().eq([
+            Component::Prefix(u16str!("C:")),
+            Component::Root,
+            Component::Normal(u16str!("a")),
+            Component::Current,
+            Component::Normal(u16str!("b")),
+        ]));
+        assert_eq!(path.components().count(), 4);
+    }
 }
@@ -0,0 +1,342 @@
+use crate::path::components::{debug_path, hash_path, Components, State};
+use crate::path::u16path;
+use crate::path::{Path, PathOwned, PathStr};
+use alloc::borrow::ToOwned;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use qp_trie::Break;
+use widestring::{u16str, U16Str, U16String};
+
+/// The set of units that separate components of a [`CustomU16Path`].
+///
+/// `/` is always written when joining paths, so the set should usually include it.
+pub trait U16Separators: 'static {
+    fn separators() -> &'static [u16];
+}
+
+/// The separators of [`U16Path`](crate::path::U16Path), `/` and `\`.
+#[derive(Debug)]
+pub struct DefaultU16Separators;
+
+impl U16Separators for DefaultU16Separators {
+    fn separators() -> &'static [u16] {
+        &[b'/' as u16, b'\\' as u16]
+    }
+}
+
+/// An owned wide path whose separators are chosen by `S`.
+///
+/// Prefixes are parsed as for [`U16PathBuf`](crate::path::U16PathBuf), with any unit
+/// of `S` accepted where a separator is expected, except in verbatim prefixes.
+#[repr(transparent)]
+pub struct CustomU16PathBuf<S: U16Separators>(PhantomData<S>, U16String);
+
+/// A borrowed wide path whose separators are chosen by `S`.
+///
+/// See [`CustomU16PathBuf`].
+#[repr(transparent)]
+pub struct CustomU16Path<S: U16Separators>(PhantomData<S>, U16Str);
+
+impl<S: U16Separators> From<&CustomU16Path<S>> for CustomU16PathBuf<S> {
+    fn from(value: &CustomU16Path<S>) -> Self {
+        Self(PhantomData, value.1.to_ustring())
+    }
+}
+
+impl<S: U16Separators> Default for CustomU16PathBuf<S> {
+    fn default() -> Self {
+        <Self as PathOwned>::new()
+    }
+}
+
+impl<S: U16Separators> From<&str> for CustomU16PathBuf<S> {
+    fn from(value: &str) -> Self {
+        Self(PhantomData, U16String::from_str(value))
+    }
+}
+
+impl<S: U16Separators> Clone for CustomU16PathBuf<S> {
+    fn clone(&self) -> Self {
+        Self(PhantomData, self.1.clone())
+    }
+}
+
+impl<S: U16Separators> Borrow<CustomU16Path<S>> for CustomU16PathBuf<S> {
+    fn borrow(&self) -> &CustomU16Path<S> {
+        CustomU16Path::from_str(&self.1)
+    }
+}
+
+impl<S: U16Separators> Deref for CustomU16PathBuf<S> {
+    type Target = CustomU16Path<S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.borrow()
+    }
+}
+
+impl<S: U16Separators> AsRef<CustomU16Path<S>> for CustomU16PathBuf<S> {
+    fn as_ref(&self) -> &CustomU16Path<S> {
+        self.borrow()
+    }
+}
+
+impl<S: U16Separators> ToOwned for CustomU16Path<S> {
+    type Owned = CustomU16PathBuf<S>;
+
+    fn to_owned(&self) -> Self::Owned {
+        CustomU16PathBuf::from(self)
+    }
+}
+
+impl<S: U16Separators> fmt::Debug for CustomU16Path<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_path("CustomU16Path", self, f)
+    }
+}
+
+impl<S: U16Separators> fmt::Debug for CustomU16PathBuf<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomU16PathBuf").field(&self.1).finish()
+    }
+}
+
+impl<S: U16Separators> PartialEq for CustomU16Path<S> {
+    fn eq(&self, other: &Self) -> bool {
+        // fast path for exact match
+        if self.1 == other.1 {
+            return true;
+        }
+
+        self.components() == other.components()
+    }
+}
+
+impl<S: U16Separators> Eq for CustomU16Path<S> {}
+
+impl<S: U16Separators> PartialEq for CustomU16PathBuf<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl<S: U16Separators> Eq for CustomU16PathBuf<S> {}
+
+impl<S: U16Separators> Hash for CustomU16Path<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_path(self, state)
+    }
+}
+
+impl<S: U16Separators> Hash for CustomU16PathBuf<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state)
+    }
+}
+
+impl<S: U16Separators> Path for CustomU16Path<S> {
+    type Str = U16Str;
+    type Owned = CustomU16PathBuf<S>;
+
+    const CURRENT_DIR: &'static U16Str = u16str!(".");
+    const PARENT_DIR: &'static U16Str = u16str!("..");
+    const SEPARATOR: &'static U16Str = u16str!("/");
+
+    fn is_separator(t: <Self::Str as PathStr>::ComponentType) -> bool {
+        S::separators().contains(&t)
+    }
+
+    fn prefix_len(path: &Self::Str) -> usize {
+        u16path::parse_prefix::<Self>(path.as_slice())
+    }
+
+    fn root() -> &'static Self {
+        Self::from_str(Self::SEPARATOR)
+    }
+
+    fn empty() -> &'static Self {
+        Self::from_str(u16str!(""))
+    }
+
+    fn has_root(&self) -> bool {
+        self.1
+            .as_slice()
+            .get(Self::prefix_len(&self.1))
+            .is_some_and(|c| Self::is_separator(*c))
+    }
+
+    /// As for [`U16Path`](crate::path::U16Path), a path is only absolute if it has
+    /// both a prefix and a root.
+    fn is_absolute(&self) -> bool {
+        Self::prefix_len(&self.1) > 0 && self.has_root()
+    }
+
+    fn components(&self) -> Components<'_, Self> {
+        Components {
+            path: &self.1,
+            prefix_len: Self::prefix_len(&self.1),
+            has_root: self.has_root(),
+            front: State::Prefix,
+            back: State::Body,
+            normalize: true,
+            pending_empty: false,
+        }
+    }
+
+    fn from_str(str: &Self::Str) -> &Self {
+        // SAFETY: CustomU16Path is repr(transparent) with U16Str
+        unsafe { &*(str as *const U16Str as *const Self) }
+    }
+
+    fn as_str(&self) -> &Self::Str {
+        &self.1
+    }
+}
+
+impl<S: U16Separators> Borrow<[u8]> for CustomU16Path<S> {
+    fn borrow(&self) -> &[u8] {
+        bytemuck::cast_slice(self.1.as_slice())
+    }
+}
+
+impl<S: U16Separators> Borrow<[u8]> for CustomU16PathBuf<S> {
+    fn borrow(&self) -> &[u8] {
+        bytemuck::cast_slice(self.1.as_slice())
+    }
+}
+
+impl<S: U16Separators> Break for CustomU16PathBuf<S> {
+    type Split = CustomU16Path<S>;
+
+    fn empty<'a>() -> &'a Self::Split {
+        CustomU16Path::empty()
+    }
+
+    fn find_break(&self, loc: usize) -> &Self::Split {
+        CustomU16Path::from_str(u16path::find_break::<CustomU16Path<S>>(&self.1, loc))
+    }
+}
+
+impl<S: U16Separators> PathOwned for CustomU16PathBuf<S> {
+    type Borrowed = CustomU16Path<S>;
+
+    fn new() -> Self {
+        Self(PhantomData, U16String::new())
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self(PhantomData, U16String::with_capacity(capacity))
+    }
+
+    fn as_path(&self) -> &Self::Borrowed {
+        self.borrow()
+    }
+
+    fn push(&mut self, component: &<Self::Borrowed as Path>::Str) {
+        u16path::push::<CustomU16Path<S>>(&mut self.1, component)
+    }
+
+    fn pop(&mut self) -> bool {
+        match self.parent().map(|parent| parent.1.len()) {
+            Some(len) => {
+                self.1.truncate(len);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn make_ascii_lowercase(&mut self) {
+        u16path::make_ascii_lowercase(self.1.as_mut_slice())
+    }
+
+    fn make_lowercase(&mut self) {
+        self.1 = u16path::to_lowercase(self.1.as_slice());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{
+        Component, CustomU16PathBuf, DefaultU16Separators, Path, PathOwned, U16PathBuf,
+        U16Separators,
+    };
+    use crate::trie::PathTrie;
+    use widestring::u16str;
+
+    struct Fullwidth;
+
+    impl U16Separators for Fullwidth {
+        fn separators() -> &'static [u16] {
+            &[b'/' as u16, b'\\' as u16, 0xFF0F]
+        }
+    }
+
+    struct SlashOnly;
+
+    impl U16Separators for SlashOnly {
+        fn separators() -> &'static [u16] {
+            &[b'/' as u16]
+        }
+    }
+
+    #[test]
+    pub fn custom_separators() {
+        let input = "a\u{FF0F}b\\c";
+        assert_eq!(U16PathBuf::from(input).components().count(), 2);
+        assert_eq!(
+            CustomU16PathBuf::<DefaultU16Separators>::from(input)
+                .components()
+                .count(),
+            2
+        );
+
+        let path = CustomU16PathBuf::<Fullwidth>::from(input);
+        assert_eq!(
+            path.components().collect::<Vec<_>>(),
+            [
+                Component::Normal(u16str!("a")),
+                Component::Normal(u16str!("b")),
+                Component::Normal(u16str!("c")),
+            ]
+        );
+        assert!(CustomU16PathBuf::<Fullwidth>::from("\u{FF0F}a").has_root());
+
+        let path = CustomU16PathBuf::<SlashOnly>::from(input);
+        assert_eq!(path.components().count(), 1);
+        assert!(!CustomU16PathBuf::<SlashOnly>::from(r"\a").has_root());
+    }
+
+    #[test]
+    pub fn custom_push_pop() {
+        let mut path = CustomU16PathBuf::<Fullwidth>::from("C:\u{FF0F}a");
+        assert!(path.is_absolute());
+        path.push(u16str!("b"));
+        assert_eq!(path.as_str(), u16str!("C:\u{FF0F}a/b"));
+        assert!(path.pop());
+        assert!(path.pop());
+        assert_eq!(path.as_str(), u16str!("C:\u{FF0F}"));
+
+        let mut path = CustomU16PathBuf::<Fullwidth>::from("C:");
+        path.push(u16str!("a"));
+        assert_eq!(path.as_str(), u16str!("C:a"));
+        assert!(path.is_relative());
+    }
+
+    #[test]
+    pub fn custom_trie() {
+        let mut trie = PathTrie::new();
+        trie.insert(CustomU16PathBuf::<Fullwidth>::from("/a\u{FF0F}b"), 1);
+
+        let lookup = CustomU16PathBuf::<Fullwidth>::from("/a/b/c");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), u16str!("/a/b"));
+
+        let lookup = CustomU16PathBuf::<Fullwidth>::from("/a/bc");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), u16str!("/a"));
+        let lookup = CustomU16PathBuf::<Fullwidth>::from("/a\u{FF0F}b\u{FF0F}c");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), u16str!("/a/b"));
+    }
+}
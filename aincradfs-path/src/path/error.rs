@@ -0,0 +1,96 @@
+use core::error::Error;
+use core::fmt;
+
+/// An error indicating that a path contained an interior NUL unit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NulError {
+    position: usize,
+}
+
+impl NulError {
+    pub(crate) fn new(position: usize) -> Self {
+        Self { position }
+    }
+
+    /// Returns the index of the NUL unit in the path.
+    pub fn nul_position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for NulError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nul unit found in path at position {}", self.position)
+    }
+}
+
+impl Error for NulError {}
+
+/// An error indicating that a path component cannot be used as a Windows file name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvalidName {
+    /// The name is a reserved device name such as `CON` or `COM1`, with or without
+    /// an extension.
+    Reserved,
+    /// The name ends in a dot or a space.
+    TrailingDotOrSpace,
+    /// The name contains one of `<>:"|?*` at `position`.
+    IllegalCharacter { position: usize },
+}
+
+impl fmt::Display for InvalidName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidName::Reserved => write!(f, "name is reserved"),
+            InvalidName::TrailingDotOrSpace => write!(f, "name ends in a dot or space"),
+            InvalidName::IllegalCharacter { position } => {
+                write!(
+                    f,
+                    "illegal character found in name at position {}",
+                    position
+                )
+            }
+        }
+    }
+}
+
+impl Error for InvalidName {}
+
+/// An error indicating that a path had a root or prefix where only a relative path
+/// is allowed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AbsolutePathError(pub(crate) ());
+
+impl fmt::Display for AbsolutePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path has a root or prefix")
+    }
+}
+
+impl Error for AbsolutePathError {}
+
+/// An error indicating that a normalized path would have more normal components than
+/// allowed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TooDeep {
+    limit: usize,
+}
+
+impl TooDeep {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+
+    /// Returns the number of normal components that was allowed.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for TooDeep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path has more than {} components", self.limit)
+    }
+}
+
+impl Error for TooDeep {}
@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+
+use crate::path::{Component, Path, PathStr};
+
+// The classic wildcard algorithm: on a mismatch, back up to the most recent star
+// and let it swallow one more element. Used both for units within a component and
+// for components within a path.
+fn wildcard<T>(
+    pattern: &[T],
+    text: &[T],
+    is_star: impl Fn(&T) -> bool,
+    matches: impl Fn(&T, &T) -> bool,
+) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        if p < pattern.len() && is_star(&pattern[p]) {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && matches(&pattern[p], &text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star, start)) = backtrack {
+            p = star + 1;
+            t = start + 1;
+            backtrack = Some((star, start + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(is_star)
+}
+
+fn matches_component<P: Path + ?Sized>(
+    pattern: &Component<'_, P>,
+    component: &Component<'_, P>,
+) -> bool {
+    match (pattern, component) {
+        (Component::Normal(pattern), Component::Normal(name)) => {
+            let star = <P::Str as PathStr>::ComponentType::from(b'*');
+            let any = <P::Str as PathStr>::ComponentType::from(b'?');
+            wildcard(
+                pattern.as_slice(),
+                name.as_slice(),
+                |c| *c == star,
+                |p, c| *p == any || p == c,
+            )
+        }
+        (pattern, component) => pattern == component,
+    }
+}
+
+pub(crate) fn matches_glob<P: Path + ?Sized>(path: &P, pattern: &P::Str) -> bool {
+    let double_star = [<P::Str as PathStr>::ComponentType::from(b'*'); 2];
+    let pattern = P::from_str(pattern).components().collect::<Vec<_>>();
+    let path = path.components().normalize().collect::<Vec<_>>();
+
+    wildcard(
+        &pattern,
+        &path,
+        |c| matches!(c, Component::Normal(name) if name.as_slice() == double_star),
+        matches_component,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{Path, U16Path, U8Path};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    fn glob(path: &str, pattern: &str) -> bool {
+        U8Path::from_str(BStr::new(path)).matches_glob(BStr::new(pattern))
+    }
+
+    #[test]
+    pub fn star() {
+        assert!(glob("/a/b.txt", "/a/*.txt"));
+        assert!(glob("/a/.txt", "/a/*.txt"));
+        assert!(glob("/a/b.txt", "/*/*"));
+        assert!(!glob("/a/b/c.txt", "/a/*.txt"));
+        assert!(!glob("/a/b.tx", "/a/*.txt"));
+        assert!(glob("/a/abcabd", "/a/*abd"));
+    }
+
+    #[test]
+    pub fn question_mark() {
+        assert!(glob("/a/b1.txt", "/a/b?.txt"));
+        assert!(!glob("/a/b.txt", "/a/b?.txt"));
+        assert!(!glob("/a/b12.txt", "/a/b?.txt"));
+    }
+
+    #[test]
+    pub fn double_star() {
+        assert!(glob("/a/b/c.txt", "**/*.txt"));
+        assert!(glob("/c.txt", "/**/c.txt"));
+        assert!(glob("/a/b/c/d", "/a/**/d"));
+        assert!(glob("/a/b/c/d", "/a/**"));
+        assert!(!glob("/a/b/c.log", "**/*.txt"));
+        assert!(!glob("/x/b/c/d", "/a/**/d"));
+    }
+
+    #[test]
+    pub fn literal() {
+        assert!(glob("/a/b", "/a/b"));
+        assert!(glob("/a/./x/../b//", "/a/b"));
+        assert!(!glob("/a/b", "a/b"));
+        assert!(!glob("/a/b", "/a/b/c"));
+        assert!(!glob("/a/bb", "/a/b"));
+    }
+
+    #[test]
+    pub fn glob_u16() {
+        let path = U16Path::from_str(u16str!(r"C:\a\b\c.txt"));
+        assert!(path.matches_glob(u16str!("**/*.txt")));
+        assert!(path.matches_glob(u16str!("C:/*/?/c.*")));
+        assert!(!path.matches_glob(u16str!("D:/**")));
+    }
+}
@@ -0,0 +1,133 @@
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::path::PathOwned;
+
+/// An owned path that computes its hash once, when it is created.
+///
+/// Hashing a path walks its components, which adds up when the same key is looked
+/// up many times. A `HashedPathBuf` hashes itself up front and feeds only that value
+/// to the hasher. Equality checks the stored hashes first and falls back to comparing
+/// components when they match.
+///
+/// The buffer can't be modified in place, since that would invalidate the hash; take
+/// it out with [`into_inner`](Self::into_inner) and wrap it again instead.
+#[derive(Clone, Debug)]
+pub struct HashedPathBuf<B: PathOwned> {
+    buf: B,
+    hash: u64,
+}
+
+impl<B: PathOwned> HashedPathBuf<B>
+where
+    B::Borrowed: Hash,
+{
+    pub fn new(buf: B) -> Self {
+        let mut hasher = FnvHasher::default();
+        buf.as_path().hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            buf,
+        }
+    }
+}
+
+impl<B: PathOwned> HashedPathBuf<B> {
+    pub fn as_path(&self) -> &B::Borrowed {
+        self.buf.as_path()
+    }
+
+    /// Returns the hash computed when this path was created.
+    pub fn cached_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: PathOwned> From<B> for HashedPathBuf<B>
+where
+    B::Borrowed: Hash,
+{
+    fn from(value: B) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<B: PathOwned> Deref for HashedPathBuf<B> {
+    type Target = B::Borrowed;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_path()
+    }
+}
+
+impl<B: PathOwned> Hash for HashedPathBuf<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash)
+    }
+}
+
+impl<B: PathOwned> PartialEq for HashedPathBuf<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.as_path() == other.as_path()
+    }
+}
+
+impl<B: PathOwned> Eq for HashedPathBuf<B> {}
+
+// 64-bit FNV-1a. The cached hash only needs to be stable and cheap; the map's own
+// hasher still mixes it when it is looked up.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{HashedPathBuf, U16PathBuf, U8PathBuf};
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn cached_hash() {
+        let a = HashedPathBuf::new(U8PathBuf::from("/a//b/./c/"));
+        let b = HashedPathBuf::new(U8PathBuf::from(r"/a/b\c"));
+        let c = HashedPathBuf::new(U8PathBuf::from("/a/b/d"));
+
+        assert_eq!(a.cached_hash(), b.cached_hash());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let fresh = HashedPathBuf::new(a.clone().into_inner());
+        assert_eq!(a.cached_hash(), fresh.cached_hash());
+    }
+
+    #[test]
+    pub fn map_lookup() {
+        let mut map = HashMap::new();
+        map.insert(HashedPathBuf::new(U16PathBuf::from(r"C:\a\b")), 1);
+
+        let key = HashedPathBuf::new(U16PathBuf::from("C:/a//b/"));
+        assert_eq!(map.get(&key), Some(&1));
+        let key = HashedPathBuf::new(U16PathBuf::from("C:/a/c"));
+        assert_eq!(map.get(&key), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! Deduplicated storage for paths that are held in many places at once.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::path::U8Path;
+
+/// A handle to a path stored in a [`PathInterner`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct PathId(u32);
+
+/// Stores each distinct path once, handing out [`PathId`]s in its place.
+///
+/// Paths are told apart the way `==` does, by component, so `/a//b` and `/a/b` are
+/// interned as the same path. The first spelling interned is the one
+/// [`resolve`](Self::resolve) gives back.
+#[derive(Default, Debug)]
+pub struct PathInterner {
+    ids: HashMap<Arc<U8Path>, PathId>,
+    paths: Vec<Arc<U8Path>>,
+}
+
+impl PathInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `path`, storing it first if it has not been interned yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` distinct paths are interned.
+    pub fn intern(&mut self, path: &U8Path) -> PathId {
+        if let Some(&id) = self.ids.get(path) {
+            return id;
+        }
+
+        let id = PathId(u32::try_from(self.paths.len()).expect("too many interned paths"));
+        let path = path.to_arc();
+        self.paths.push(Arc::clone(&path));
+        self.ids.insert(path, id);
+        id
+    }
+
+    /// Returns the id of `path` if it has been interned.
+    pub fn get(&self, path: &U8Path) -> Option<PathId> {
+        self.ids.get(path).copied()
+    }
+
+    /// Returns the path behind `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not handed out by this interner.
+    pub fn resolve(&self, id: PathId) -> &U8Path {
+        &self.paths[id.0 as usize]
+    }
+
+    /// Returns the number of distinct paths interned.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{Path, PathInterner, U8Path};
+    use bstr::BStr;
+
+    #[test]
+    pub fn intern() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        let mut interner = PathInterner::new();
+
+        let a = interner.intern(path("/a//b"));
+        assert_eq!(interner.intern(path("/a/b")), a);
+        assert_eq!(interner.intern(path(r"/a\b/./")), a);
+        assert_eq!(interner.get(path("/a/b")), Some(a));
+
+        let b = interner.intern(path("/a/c"));
+        assert_ne!(a, b);
+        assert_eq!(interner.get(path("/a")), None);
+        assert_eq!(interner.len(), 2);
+
+        assert_eq!(interner.resolve(a).as_str(), "/a//b");
+        assert_eq!(interner.resolve(b).as_str(), "/a/c");
+    }
+}
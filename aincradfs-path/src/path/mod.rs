@@ -1,34 +1,68 @@
-use std::borrow::Borrow;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::{Add, Range};
 
 use bstr::{BStr, ByteSlice};
 use bytemuck::Pod;
 use qp_trie::Break;
 use widestring::U16Str;
 
+mod builder;
+mod cached;
+mod canon;
 mod components;
+mod custom;
+mod error;
+mod glob;
+mod hashed;
+#[cfg(feature = "std")]
+mod interner;
+pub mod ops;
+mod posix;
+mod sort;
+#[cfg(feature = "std")]
+mod std_path;
 mod u16path;
 mod u8path;
+mod validate;
 
-pub use u16path::{U16Path, U16PathBuf};
-pub use u8path::{U8Path, U8PathBuf};
-pub use components::{Components, Component};
-
-pub trait PathBuf {
-    fn new() -> Self;
-    fn push<A>(&mut self, entry: A)
-    where
-        A: Borrow<A>;
-
-    fn pop(&mut self);
-}
+pub use builder::PathBuilder;
+pub use cached::CachedPathBuf;
+pub use canon::CanonPath;
+pub use components::{Component, Components, ComponentsIndexed, Iter, Normalize, RawComponents};
+pub use custom::{CustomU16Path, CustomU16PathBuf, DefaultU16Separators, U16Separators};
+pub use error::{AbsolutePathError, InvalidName, NulError, TooDeep};
+pub use hashed::HashedPathBuf;
+#[cfg(feature = "std")]
+pub use interner::{PathId, PathInterner};
+pub use posix::{PosixU8Path, PosixU8PathBuf};
+pub use sort::SortKey;
+pub use u16path::{AsU16Path, U16Path, U16PathBuf};
+pub use u8path::{AsU8Path, U8Path, U8PathBuf};
 
 pub trait PathStr: 'static + PartialEq {
-    type ComponentType: Copy + PartialEq + Pod;
+    type ComponentType: Copy + Ord + Pod + From<u8> + Add<Output = Self::ComponentType>;
 
     fn as_slice(&self) -> &[Self::ComponentType];
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool;
     fn from_slice(slice: &[Self::ComponentType]) -> &Self;
+
+    /// Returns `true` if `other` is a prefix of this string, unit by unit.
+    fn starts_with(&self, other: &Self) -> bool {
+        self.as_slice().starts_with(other.as_slice())
+    }
+
+    /// Divides the string in two at `mid`, counted in code units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    fn split_at(&self, mid: usize) -> (&Self, &Self) {
+        let (head, tail) = self.as_slice().split_at(mid);
+        (Self::from_slice(head), Self::from_slice(tail))
+    }
 }
 
 impl PathStr for U16Str {
@@ -71,16 +105,130 @@ impl PathStr for BStr {
     }
 }
 
+/// An owned, growable path buffer.
 pub trait PathOwned: Break + Clone + Borrow<[u8]> {
     type Borrowed: Path + ?Sized;
 
+    /// Creates an empty path.
     fn new() -> Self;
+
+    /// Creates an empty path with room for `capacity` code units.
+    fn with_capacity(capacity: usize) -> Self;
+    fn as_path(&self) -> &Self::Borrowed;
+
+    /// Builds a path from a sequence of components.
+    ///
+    /// Components are joined with the canonical separator, so collecting a path's own
+    /// components produces a copy with repeated separators and `.` removed.
+    fn from_components<'a, I>(components: I) -> Self
+    where
+        I: IntoIterator<Item = Component<'a, Self::Borrowed>>,
+    {
+        let separator = <Self::Borrowed as Path>::SEPARATOR.as_slice();
+        let mut root = [separator[0]];
+        let mut buf = Vec::new();
+        let mut needs_separator = false;
+        let mut after_prefix = false;
+
+        for component in components {
+            if needs_separator {
+                buf.extend_from_slice(separator);
+            }
+
+            let (part, separated) = match component {
+                // a prefix or root runs straight into whatever follows it
+                Component::Prefix(prefix) => (prefix.as_slice(), false),
+                Component::Root if after_prefix => {
+                    root[0] = root_after_prefix::<Self::Borrowed>(&mut buf);
+                    (&root[..], false)
+                }
+                Component::Root => (separator, false),
+                Component::Current => (<Self::Borrowed as Path>::CURRENT_DIR.as_slice(), true),
+                Component::Parent => (<Self::Borrowed as Path>::PARENT_DIR.as_slice(), true),
+                Component::Normal(name) => (name.as_slice(), true),
+                Component::Empty => (&[][..], true),
+            };
+            after_prefix = matches!(component, Component::Prefix(_));
+            buf.extend_from_slice(part);
+            needs_separator = separated;
+        }
+
+        let mut path = Self::new();
+        path.push(PathStr::from_slice(&buf));
+        path
+    }
+
+    /// Extends `self` with `component`.
+    ///
+    /// If `component` has a root, it replaces the current path entirely.
     fn push(&mut self, component: &<Self::Borrowed as Path>::Str);
-    fn pop(&mut self);
+
+    /// Truncates `self` to its parent, returning `false` if there is no parent.
+    fn pop(&mut self) -> bool;
+
+    /// Converts the path to its ASCII lower case equivalent in-place.
+    fn make_ascii_lowercase(&mut self);
+
+    /// Converts the path to its Unicode lower case equivalent in-place.
+    ///
+    /// Code units that are not valid characters are left as they are. The path may
+    /// change length.
+    fn make_lowercase(&mut self);
+
+    /// Replaces the final component of the path with `file_name`.
+    ///
+    /// If the path has no file name, this is equivalent to pushing `file_name`.
+    fn set_file_name(&mut self, file_name: &<Self::Borrowed as Path>::Str) {
+        if self.as_path().file_name().is_some() {
+            let popped = self.pop();
+            debug_assert!(popped);
+        }
+        self.push(file_name);
+    }
+
+    /// Replaces or appends the extension of the final component.
+    ///
+    /// Returns `false` and does nothing if the path has no file name. An empty
+    /// `extension` removes the existing extension.
+    fn set_extension(&mut self, extension: &<Self::Borrowed as Path>::Str) -> bool {
+        let mut file_name = match self.as_path().file_stem() {
+            None => return false,
+            Some(stem) => stem.as_slice().to_vec(),
+        };
+
+        if !extension.is_empty() {
+            file_name.push(<Self::Borrowed as Path>::CURRENT_DIR.as_slice()[0]);
+            file_name.extend_from_slice(extension.as_slice());
+        }
+
+        self.set_file_name(PathStr::from_slice(&file_name));
+        true
+    }
+
+    /// Removes the final normal component of the path and returns it as a path of
+    /// its own.
+    ///
+    /// Returns `None` and leaves the path alone if it does not end in a normal
+    /// component, such as `/` or `a/..`.
+    fn pop_component(&mut self) -> Option<Self> {
+        let mut name = Self::new();
+        name.push(self.as_path().file_name()?);
+        let popped = self.pop();
+        debug_assert!(popped);
+        Some(name)
+    }
+
+    /// Pops components until the path has at most `depth` normal components.
+    ///
+    /// Any root or prefix is kept, so truncating `/a/b` to depth `0` gives `/`.
+    fn truncate_to_depth(&mut self, depth: usize) {
+        while self.as_path().depth() > depth && self.pop() {}
+    }
 }
 
-pub trait Path: PartialEq + Eq {
+pub trait Path: 'static + PartialEq + Eq {
     type Str: PathStr + ?Sized;
+    type Owned: PathOwned<Borrowed = Self>;
 
     const CURRENT_DIR: &'static Self::Str;
     const PARENT_DIR: &'static Self::Str;
@@ -88,11 +236,1934 @@ pub trait Path: PartialEq + Eq {
 
     fn is_separator(t: <Self::Str as PathStr>::ComponentType) -> bool;
 
+    /// Returns the index of the first separator in `path`.
+    fn find_separator(path: &Self::Str) -> Option<usize> {
+        path.as_slice().iter().position(|c| Self::is_separator(*c))
+    }
+
+    /// Returns the index of the last separator in `path`.
+    fn rfind_separator(path: &Self::Str) -> Option<usize> {
+        path.as_slice().iter().rposition(|c| Self::is_separator(*c))
+    }
+
+    /// Returns the number of separator units in `path`, including repeated ones.
+    fn count_separators(path: &Self::Str) -> usize {
+        path.as_slice()
+            .iter()
+            .filter(|c| Self::is_separator(**c))
+            .count()
+    }
+
+    /// Returns the number of separator units in the path, including repeated and
+    /// trailing ones.
+    ///
+    /// This scans the raw path, so it is not the number of components.
+    fn separator_count(&self) -> usize {
+        Self::count_separators(self.as_str())
+    }
+
+    /// Returns the length of the prefix (such as a Windows drive) at the start of `path`.
+    ///
+    /// Paths without a notion of prefixes always return 0.
+    fn prefix_len(_path: &Self::Str) -> usize {
+        0
+    }
+
     fn root() -> &'static Self;
     fn empty() -> &'static Self;
 
     fn has_root(&self) -> bool;
-    fn components(&self) -> Components<Self>;
+
+    /// Returns `true` if the path is independent of the current directory.
+    ///
+    /// By default this is the same as [`Path::has_root`].
+    fn is_absolute(&self) -> bool {
+        self.has_root()
+    }
+
+    /// Returns `true` if the path is not absolute.
+    fn is_relative(&self) -> bool {
+        !self.is_absolute()
+    }
+
+    /// Returns the number of normal components in the path.
+    fn depth(&self) -> usize {
+        self.components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .count()
+    }
+
+    /// Returns `true` if the path is `/` with nothing before or after it.
+    ///
+    /// A root after a prefix, such as `C:\`, is not the root.
+    fn is_root(&self) -> bool {
+        let mut comps = self.components();
+        comps.next() == Some(Component::Root) && comps.next().is_none()
+    }
+
+    /// Returns `true` if the path is the empty string.
+    ///
+    /// This only checks the length, so `.` is not empty.
+    fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+
+    /// Returns `true` if the path is equal to [`Path::empty`].
+    fn is_empty_path(&self) -> bool {
+        self == Self::empty()
+    }
+
+    /// Returns `true` if the path is equal to [`Path::root`].
+    ///
+    /// An alias of [`Path::is_root`], which is the one to prefer.
+    fn is_the_root(&self) -> bool {
+        self.is_root()
+    }
+
+    fn components(&self) -> Components<'_, Self>;
+
+    /// Returns the `n`th component of the path, counting from zero.
+    fn nth_component(&self, n: usize) -> Option<Component<'_, Self>> {
+        self.components().nth(n)
+    }
+
+    /// Returns where the `n`th component lies in the path, in code units.
+    ///
+    /// The range can be used to slice [`Path::as_str`]. For the root, this is the
+    /// separator that makes the path rooted.
+    fn component_range(&self, n: usize) -> Option<Range<usize>> {
+        self.components_indexed().nth(n).map(|(_, range)| range)
+    }
+
+    /// Returns an iterator over the components of the path, each with the range of
+    /// code units it occupies in the path.
+    ///
+    /// See [`ComponentsIndexed`].
+    fn components_indexed(&self) -> ComponentsIndexed<'_, Self> {
+        ComponentsIndexed {
+            inner: self.components(),
+            base: self.as_str(),
+        }
+    }
+
+    /// Splits the path into its first component and the rest of the path.
+    fn split_first(&self) -> Option<(Component<'_, Self>, &Self)> {
+        let mut comps = self.components();
+        let first = comps.next()?;
+        Some((first, comps.as_path()))
+    }
+
+    /// Splits the path into everything before its final component, and that component.
+    fn split_last(&self) -> Option<(&Self, Component<'_, Self>)> {
+        let mut comps = self.components();
+        let last = comps.next_back()?;
+        Some((comps.as_path(), last))
+    }
+
+    /// Returns an iterator over the components of the path as strings.
+    ///
+    /// See [`Component::as_str`].
+    fn iter(&self) -> Iter<'_, Self> {
+        Iter {
+            inner: self.components(),
+        }
+    }
+
+    /// Returns an iterator over the separator-delimited segments of the path, without
+    /// any normalization.
+    ///
+    /// See [`RawComponents`].
+    fn raw_components(&self) -> RawComponents<'_, Self> {
+        RawComponents::new(self.as_str())
+    }
 
     fn from_str(str: &Self::Str) -> &Self;
+    fn as_str(&self) -> &Self::Str;
+
+    /// Copies the path into a new owned buffer.
+    fn to_path_buf(&self) -> Self::Owned {
+        let mut buf = Self::Owned::new();
+        buf.push(self.as_str());
+        buf
+    }
+
+    /// Copies `str` into a new owned path, for code that is generic over the encoding.
+    fn to_owned_from_str(str: &Self::Str) -> Self::Owned {
+        Self::from_str(str).to_path_buf()
+    }
+
+    /// Returns an owned copy of the path with every separator rewritten to
+    /// [`Path::SEPARATOR`].
+    ///
+    /// Components, including `.` and `..`, are left untouched, as is any prefix, since
+    /// verbatim prefixes depend on their separators.
+    fn canonical_separators(&self) -> Self::Owned {
+        let separator = Self::SEPARATOR.as_slice()[0];
+        let mut buf = self.as_str().as_slice().to_vec();
+        for c in &mut buf[Self::prefix_len(self.as_str())..] {
+            if Self::is_separator(*c) {
+                *c = separator;
+            }
+        }
+
+        let mut path = Self::Owned::new();
+        path.push(PathStr::from_slice(&buf));
+        path
+    }
+
+    /// Joins `other` onto the path and normalizes the result, returning `None` if it
+    /// would end up outside the path.
+    ///
+    /// `other` is rejected if it climbs out with `..` (see [`Path::escapes_root`]),
+    /// and also if it has a root or prefix, rather than being re-rooted under the path.
+    fn join_contained(&self, other: &Self::Str) -> Option<Self::Owned> {
+        let other_path = Self::from_str(other);
+        if other_path.has_root() || Self::prefix_len(other) > 0 || other_path.escapes_root() {
+            return None;
+        }
+
+        let mut joined = self.to_path_buf();
+        joined.push(other);
+        Some(joined.as_path().normalize())
+    }
+
+    /// Returns an owned copy of the path with its file name replaced.
+    ///
+    /// See [`PathOwned::set_file_name`].
+    fn with_file_name(&self, file_name: &Self::Str) -> Self::Owned {
+        let mut buf = self.to_path_buf();
+        buf.set_file_name(file_name);
+        buf
+    }
+
+    /// Returns an owned copy of the path with its extension replaced.
+    ///
+    /// See [`PathOwned::set_extension`].
+    fn with_extension(&self, extension: &Self::Str) -> Self::Owned {
+        let mut buf = self.to_path_buf();
+        buf.set_extension(extension);
+        buf
+    }
+
+    /// Returns `true` if `base` is a prefix of the path, compared component-wise.
+    ///
+    /// `/a/b` starts with `/a`, but `/ab` does not.
+    fn starts_with(&self, base: &Self) -> bool {
+        let mut comps = self.components();
+        base.components().all(|c| comps.next() == Some(c))
+    }
+
+    /// Lexically normalizes the path.
+    ///
+    /// `.` and repeated separators are removed, `..` removes the preceding normal
+    /// component, and separators are rewritten to [`Path::SEPARATOR`]. A `..` directly
+    /// after the root is dropped, while a leading `..` in a relative path is kept.
+    /// Symbolic links are not taken into account.
+    ///
+    /// This always allocates. Prefer [`Path::normalized`], which borrows `self`
+    /// instead when the path is already normal.
+    fn normalize(&self) -> Self::Owned {
+        Self::Owned::from_components(self.components().normalize())
+    }
+
+    /// Returns `true` if [`Path::normalize`] would leave a leading `..`, meaning the
+    /// path climbs above the directory it is resolved against.
+    ///
+    /// `a/../../b` escapes, while `a/b/../c` does not. A path with a root never
+    /// escapes, since `..` stops at the root.
+    fn escapes_root(&self) -> bool {
+        if self.has_root() {
+            return false;
+        }
+
+        let mut depth = 0usize;
+        for component in self.components() {
+            match component {
+                Component::Normal(_) => depth += 1,
+                Component::Parent if depth == 0 => return true,
+                Component::Parent => depth -= 1,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Returns the normalized path, borrowing `self` if it is already normal.
+    ///
+    /// This is the preferred way to get a clean path: it checks
+    /// [`Path::is_normalized`] without allocating, and only builds a new path with
+    /// [`Path::normalize`] when something has to change.
+    fn normalized(&self) -> Cow<'_, Self>
+    where
+        Self: ToOwned<Owned = <Self as Path>::Owned>,
+    {
+        if self.is_normalized() {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.normalize())
+        }
+    }
+
+    /// The same as [`Path::normalized`].
+    #[deprecated(note = "use Path::normalized")]
+    fn normalize_cow(&self) -> Cow<'_, Self>
+    where
+        Self: ToOwned<Owned = <Self as Path>::Owned>,
+    {
+        self.normalized()
+    }
+
+    /// Returns the path as a canonical byte sequence, suitable as a key outside the crate.
+    ///
+    /// Repeated separators and `.` are removed and separators are rewritten to
+    /// [`Path::SEPARATOR`], so paths that compare equal give identical bytes. `..` is
+    /// kept. Wider code units are written little-endian.
+    fn normalized_bytes(&self) -> Vec<u8> {
+        let key = Self::Owned::from_components(self.components());
+        let mut bytes =
+            Vec::with_capacity(core::mem::size_of_val(key.as_path().as_str().as_slice()));
+        for unit in key.as_path().as_str().as_slice() {
+            let start = bytes.len();
+            bytes.extend_from_slice(bytemuck::bytes_of(unit));
+            if cfg!(target_endian = "big") {
+                bytes[start..].reverse();
+            }
+        }
+        bytes
+    }
+
+    /// Returns the relative path that leads from `base` to this path.
+    ///
+    /// Both paths are normalized first, so joining the result onto `base` and
+    /// normalizing gives back this path. Returns `None` unless both paths are absolute
+    /// and share the same prefix.
+    fn relative_to(&self, base: &Self) -> Option<Self::Owned> {
+        if !self.is_absolute() || !base.is_absolute() {
+            return None;
+        }
+
+        let path = self.normalize();
+        let base = base.normalize();
+        let mut path = path.as_path().components().peekable();
+        let mut base = base.as_path().components().peekable();
+
+        while let (Some(a), Some(b)) = (path.peek(), base.peek()) {
+            if a != b {
+                break;
+            }
+            path.next();
+            base.next();
+        }
+
+        let mut relative = Vec::new();
+        for component in base {
+            match component {
+                Component::Normal(_) => relative.push(Component::Parent),
+                // the prefixes or roots differ
+                _ => return None,
+            }
+        }
+        relative.extend(path);
+        Some(Self::Owned::from_components(relative))
+    }
+
+    /// Returns `true` if the path matches the glob `pattern`.
+    ///
+    /// The pattern is matched against the normalized components of the path. Within a
+    /// component, `*` matches any run of units and `?` matches exactly one, while a
+    /// component that is just `**` matches any number of whole components, including
+    /// a root or prefix. So `**/*.txt` matches `/a/b/c.txt`, but `*.txt` does not.
+    fn matches_glob(&self, pattern: &Self::Str) -> bool {
+        glob::matches_glob(self, pattern)
+    }
+
+    /// Checks that `name` can be used as a file name on Windows.
+    ///
+    /// Reserved device names (`CON`, `PRN`, `AUX`, `NUL`, `COM1` to `COM9` and `LPT1` to
+    /// `LPT9`, in any case and with any extension), names ending in a dot or space, and
+    /// names containing any of `<>:"|?*` are rejected.
+    fn validate_component(name: &Self::Str) -> Result<(), InvalidName> {
+        validate::validate_component::<Self>(name)
+    }
+
+    /// Checks every normal component of the path with [`Path::validate_component`].
+    fn validate(&self) -> Result<(), InvalidName> {
+        validate::validate(self)
+    }
+
+    /// Returns `true` if `child` is exactly one component below the path.
+    ///
+    /// `/a` is the parent of `/a/b`, but not of `/a/b/c` or `/ab`.
+    fn is_parent_of(&self, child: &Self) -> bool {
+        let mut theirs = child.components();
+        self.components().all(|c| theirs.next() == Some(c))
+            && theirs.next().is_some()
+            && theirs.next().is_none()
+    }
+
+    /// Returns `true` if the path is below `ancestor` by one or more components.
+    ///
+    /// This is [`Path::starts_with`] without the case where the paths are equal, so
+    /// `/a/b/c` is a descendant of `/a`, but `/a` is not a descendant of itself.
+    fn is_descendant_of(&self, ancestor: &Self) -> bool {
+        let mut ours = self.components();
+        ancestor.components().all(|c| ours.next() == Some(c)) && ours.next().is_some()
+    }
+
+    /// Returns the path without its final component, if there is one.
+    fn parent(&self) -> Option<&Self> {
+        let mut comps = self.components();
+        match comps.next_back() {
+            Some(Component::Normal(_) | Component::Current | Component::Parent) => {
+                Some(comps.as_path())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the final component of the path, if it is a normal file or directory name.
+    fn file_name(&self) -> Option<&Self::Str> {
+        match self.components().next_back() {
+            Some(Component::Normal(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the file name without its final extension.
+    fn file_stem(&self) -> Option<&Self::Str> {
+        self.file_name()
+            .map(rsplit_file_at_dot::<Self>)
+            .and_then(|(before, after)| before.or(after))
+    }
+
+    /// Returns the final extension of the file name, if there is one.
+    fn extension(&self) -> Option<&Self::Str> {
+        self.file_name()
+            .map(rsplit_file_at_dot::<Self>)
+            .and_then(|(before, after)| before.and(after))
+    }
+
+    /// Splits off the final extension, returning the path without it and the extension.
+    ///
+    /// `/a/b.tar.gz` gives `/a/b.tar` and `gz`. A path without an extension, such as
+    /// `/a/.hidden`, is returned whole with `None`.
+    fn strip_extension(&self) -> (&Self, Option<&Self::Str>) {
+        let extension = match self.extension() {
+            Some(extension) => extension,
+            None => return (self, None),
+        };
+
+        // the extension borrows from the path, so its offset gives the dot's position
+        let slice = self.as_str().as_slice();
+        let offset = (extension.as_slice().as_ptr() as usize - slice.as_ptr() as usize)
+            / core::mem::size_of::<<Self::Str as PathStr>::ComponentType>();
+        let stem = Self::from_str(PathStr::from_slice(&slice[..offset - 1]));
+        (stem, Some(extension))
+    }
+
+    /// Returns the path with any leading root removed, borrowed from `self`.
+    ///
+    /// Paths with a prefix are returned unchanged, since their root belongs to the
+    /// prefix.
+    fn without_root(&self) -> &Self {
+        if Self::prefix_len(self.as_str()) > 0 {
+            return self;
+        }
+
+        let slice = self.as_str().as_slice();
+        let start = slice
+            .iter()
+            .position(|&c| !Self::is_separator(c))
+            .unwrap_or(slice.len());
+        Self::from_str(PathStr::from_slice(&slice[start..]))
+    }
+
+    /// Returns an owned copy of the path with a root, adding a separator after any
+    /// prefix if the path does not already have one.
+    fn with_root(&self) -> Self::Owned {
+        if self.has_root() {
+            return self.to_path_buf();
+        }
+
+        let slice = self.as_str().as_slice();
+        let (prefix, rest) = slice.split_at(Self::prefix_len(self.as_str()));
+        let mut buf = Vec::with_capacity(slice.len() + 1);
+        buf.extend_from_slice(prefix);
+        buf.extend_from_slice(Self::SEPARATOR.as_slice());
+        buf.extend_from_slice(rest);
+
+        let mut path = Self::Owned::new();
+        path.push(PathStr::from_slice(&buf));
+        path
+    }
+
+    /// Returns the longest prefix of whole components that the path shares with
+    /// `other`, borrowed from `self`.
+    ///
+    /// `/a/b/c` and `/a/b/d` share `/a/b`, while `/a` and `/x` share only `/`, and
+    /// relative paths with nothing in common share the empty path.
+    fn common_prefix(&self, other: &Self) -> &Self {
+        let shared = self
+            .components()
+            .zip(other.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let end = match shared.checked_sub(1) {
+            Some(last) => self.component_range(last).map_or(0, |range| range.end),
+            None => 0,
+        };
+        Self::from_str(PathStr::from_slice(&self.as_str().as_slice()[..end]))
+    }
+
+    /// Returns `true` if the path is already in the form [`Path::normalize`] gives.
+    ///
+    /// That is, it has no `.` components, no `..` that normalizing would remove, no
+    /// repeated or trailing separators, and only uses [`Path::SEPARATOR`], apart from
+    /// the backslash root a verbatim prefix needs. This only allocates to check such
+    /// a root.
+    fn is_normalized(&self) -> bool {
+        is_lexically_normal(self)
+    }
+
+    /// Returns the path with any trailing separators removed, borrowed from `self`.
+    ///
+    /// A root is kept, so `/` and `C:\` are returned unchanged.
+    fn trim_trailing_separators(&self) -> &Self {
+        let slice = self.as_str().as_slice();
+        let min = root_end(self);
+        let end = slice[min..]
+            .iter()
+            .rposition(|&c| !Self::is_separator(c))
+            .map_or(min, |i| min + i + 1);
+        Self::from_str(PathStr::from_slice(&slice[..end]))
+    }
+
+    /// Returns `true` if the path ends in a separator, such as `/a/`.
+    ///
+    /// The separator of a root on its own, as in `/`, does not count.
+    fn has_trailing_separator(&self) -> bool {
+        let slice = self.as_str().as_slice();
+        slice.len() > root_end(self) && slice.last().is_some_and(|&c| Self::is_separator(c))
+    }
+
+    /// Returns `true` if the path's extension is `extension`, comparing ASCII letters
+    /// without regard to case if `case_insensitive` is set.
+    ///
+    /// A path without an extension never matches.
+    fn has_extension(&self, extension: &Self::Str, case_insensitive: bool) -> bool {
+        match self.extension() {
+            Some(ours) if case_insensitive => {
+                eq_ignore_ascii_case(ours.as_slice(), extension.as_slice())
+            }
+            Some(ours) => ours == extension,
+            None => false,
+        }
+    }
+
+    /// Returns a key that orders paths by their file names, ignoring ASCII case but
+    /// breaking ties by case. See [`SortKey`].
+    ///
+    /// A path without a file name sorts as an empty name.
+    fn sort_key(&self) -> SortKey<'_, Self::Str> {
+        SortKey(self.file_name().unwrap_or(PathStr::from_slice(&[])))
+    }
+
+    /// Returns the rest of the path after `base`, if the path starts with `base`
+    /// component-wise. See [`Path::starts_with`].
+    ///
+    /// The result is borrowed from `self`, with any separators after `base` skipped.
+    fn strip_prefix(&self, base: &Self) -> Option<&Self> {
+        let mut comps = self.components();
+        for component in base.components() {
+            if comps.next() != Some(component) {
+                return None;
+            }
+        }
+        Some(comps.as_path())
+    }
+
+    /// Replaces `old_base` at the start of the path with `new_base`, or returns `None`
+    /// if the path does not start with `old_base`.
+    ///
+    /// Rebasing `/mnt/a/file` from `/mnt/a` to `/data/b` gives `/data/b/file`.
+    fn rebase(&self, old_base: &Self, new_base: &Self) -> Option<Self::Owned> {
+        let rest = self.strip_prefix(old_base)?;
+        let mut path = new_base.to_path_buf();
+        if !rest.is_empty_path() {
+            path.push(rest.as_str());
+        }
+        Some(path)
+    }
+
+    /// Returns an iterator over the components of the path, leaving out the file name.
+    ///
+    /// This yields the same components as the path's [`parent`](Path::parent) would,
+    /// but a path like `a/..` that ends in something other than a normal component
+    /// yields all of its components.
+    fn dir_components(&self) -> Components<'_, Self> {
+        let comps = self.components();
+        let mut dir = comps.clone();
+        match dir.next_back() {
+            Some(Component::Normal(_)) => dir,
+            _ => comps,
+        }
+    }
+
+    /// Rebuilds the path with `f` applied to the name of every normal component.
+    ///
+    /// Other components are kept as they are, and the result is joined like
+    /// [`PathOwned::from_components`]. A name that `f` gives back with a separator in
+    /// it becomes more than one component of the result.
+    fn map_components<'a, F>(&'a self, mut f: F) -> Self::Owned
+    where
+        Self::Str: ToOwned,
+        F: FnMut(&'a Self::Str) -> Cow<'a, Self::Str>,
+    {
+        let names = self
+            .components()
+            .map(|component| match component {
+                Component::Normal(name) => Some(f(name)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let components = self.components().zip(&names).map(|pair| match pair {
+            (Component::Normal(_), Some(name)) => Component::Normal(name.as_ref()),
+            (component, _) => component,
+        });
+        Self::Owned::from_components(components)
+    }
+
+    /// Returns `true` if the path contains a NUL unit.
+    fn contains_nul(&self) -> bool {
+        self.as_str().as_slice().contains(&0.into())
+    }
+
+    /// Copies the path into a new owned buffer, checking that it contains no NUL
+    /// units so that it is not truncated when handed to a C or Win32 API.
+    fn to_c_compatible(&self) -> Result<Self::Owned, NulError> {
+        match self.as_str().as_slice().iter().position(|&c| c == 0.into()) {
+            Some(position) => Err(NulError::new(position)),
+            None => Ok(self.to_path_buf()),
+        }
+    }
+
+    /// Copies the path and pushes each of `parts` onto it in turn.
+    ///
+    /// As with [`PathOwned::push`], a part with a root replaces everything before it.
+    fn join_many<'a, I>(&self, parts: I) -> Self::Owned
+    where
+        I: IntoIterator<Item = &'a Self::Str>,
+    {
+        let mut path = self.to_path_buf();
+        for part in parts {
+            path.push(part);
+        }
+        path
+    }
+
+    /// Returns the prefix and root of the path, or an empty path if it has neither.
+    ///
+    /// This is the part of the path that comes before the first normal component.
+    fn root_prefix(&self) -> &Self {
+        Self::from_str(self.as_str().split_at(root_end(self)).0)
+    }
+
+    /// Normalizes the path as [`Path::normalize`] does, also returning whether the
+    /// original ended in a separator.
+    ///
+    /// Normalizing drops a trailing separator, so this keeps the hint that the path
+    /// names a directory. The separator of a root on its own does not count.
+    fn normalize_with_kind(&self) -> (Self::Owned, bool) {
+        (self.normalize(), self.has_trailing_separator())
+    }
+
+    /// Makes the path absolute by joining it onto `base`, normalizing the result.
+    ///
+    /// An absolute path is only normalized. A path with a root but no prefix, such as
+    /// `\a` on Windows, takes the prefix of `base` rather than replacing it.
+    fn absolutize(&self, base: &Self) -> Self::Owned {
+        if self.is_absolute() {
+            return self.normalize();
+        }
+
+        let mut path = if self.has_root() {
+            let prefix = base.as_str().split_at(Self::prefix_len(base.as_str())).0;
+            Self::from_str(prefix).to_path_buf()
+        } else {
+            base.to_path_buf()
+        };
+        path.push(self.as_str());
+        path.as_path().normalize()
+    }
+
+    /// Returns the relative part of the path left after skipping its first `skip`
+    /// components, borrowed from `self`.
+    ///
+    /// Any prefix and root are always dropped, so `/a/b/c/d` with `skip` of 2 gives
+    /// `c/d`. Skipping past the end gives an empty path.
+    fn tail_from(&self, skip: usize) -> &Self {
+        let mut components = self.components().skip_prefix();
+        for _ in 0..skip {
+            if components.next().is_none() {
+                break;
+            }
+        }
+        components.as_path()
+    }
+
+    /// Returns every directory above the path, shortest first.
+    ///
+    /// These are the paths reached by repeatedly taking [`Path::parent`], so
+    /// `/a/b/c.txt` gives `/`, `/a` and `/a/b`. The empty parent of a relative path
+    /// such as `a` is left out.
+    fn dir_prefixes(&self) -> impl Iterator<Item = &Self> {
+        let mut prefixes = core::iter::successors(self.parent(), |path| path.parent())
+            .filter(|path| !path.is_empty())
+            .collect::<Vec<_>>();
+        prefixes.reverse();
+        prefixes.into_iter()
+    }
+
+    /// Returns the first component of the path, which may be a prefix, root or `.`.
+    fn first_component(&self) -> Option<Component<'_, Self>> {
+        self.components().next()
+    }
+
+    /// Returns the last component of the path, of whatever kind.
+    ///
+    /// Unlike [`Path::file_name`], this also returns a root, `.` or `..` at the end.
+    fn last_component(&self) -> Option<Component<'_, Self>> {
+        self.components().next_back()
+    }
+
+    /// Normalizes the path as [`Path::normalize`] does, failing if the result would
+    /// have more than `max_components` normal components.
+    ///
+    /// The components are counted in a first pass that allocates nothing, so a path
+    /// that is too deep is rejected before any allocation, and one that fits is then
+    /// parsed again to normalize it. The count cannot stop early, since a later `..`
+    /// may bring the path back under the limit.
+    fn normalize_bounded(&self, max_components: usize) -> Result<Self::Owned, TooDeep> {
+        let mut depth = 0usize;
+        for component in self.components() {
+            match component {
+                Component::Normal(_) => depth += 1,
+                Component::Parent => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        if depth > max_components {
+            return Err(TooDeep::new(max_components));
+        }
+        Ok(self.normalize())
+    }
+
+    /// Splits the path into the directory holding its file name and the file name,
+    /// parsing the path only once.
+    ///
+    /// When the path ends in a normal component, this matches [`Path::parent`] and
+    /// [`Path::file_name`]. Otherwise, as for `/` or `a/..`, the whole path is
+    /// returned as the directory.
+    fn split_file(&self) -> (&Self, Option<&Self::Str>) {
+        let mut comps = self.components();
+        match comps.next_back() {
+            Some(Component::Normal(name)) => (comps.as_path(), Some(name)),
+            _ => (self, None),
+        }
+    }
+
+    /// Returns `true` if every unit of the path is below `0x80`.
+    ///
+    /// ASCII-only paths can be compared and case-folded with the cheap ASCII routines.
+    fn is_ascii(&self) -> bool {
+        self.as_str().as_slice().iter().all(|&c| c < 0x80.into())
+    }
+}
+
+// A guess at how long a component is, used to reserve room when extending a path
+// from an iterator. Most names in a path are short, and a separator comes with each.
+pub(crate) const COMPONENT_LEN_HINT: usize = 8;
+
+// Maps an ASCII upper case letter to lower case, leaving any other unit alone.
+pub(crate) fn fold_ascii<T: Copy + PartialOrd + From<u8> + Add<Output = T>>(unit: T) -> T {
+    if unit >= T::from(b'A') && unit <= T::from(b'Z') {
+        unit + T::from(32)
+    } else {
+        unit
+    }
+}
+
+// Compares two strings, treating ASCII letters of either case as equal.
+fn eq_ignore_ascii_case<T: Copy + PartialOrd + From<u8> + Add<Output = T>>(
+    a: &[T],
+    b: &[T],
+) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(&a, &b)| fold_ascii(a) == fold_ascii(b))
+}
+
+// Returns the length of the path's prefix and root, if any.
+fn root_end<P: Path + ?Sized>(path: &P) -> usize {
+    P::prefix_len(path.as_str()) + usize::from(path.has_root())
+}
+
+// Returns the unit `Path::normalize` writes as the root after the prefix in `prefix`,
+// leaving `prefix` as it was. This is `Path::SEPARATOR` unless the prefix would read
+// it as part of itself, since verbatim prefixes only end at a backslash.
+fn root_after_prefix<P: Path + ?Sized>(
+    prefix: &mut Vec<<P::Str as PathStr>::ComponentType>,
+) -> <P::Str as PathStr>::ComponentType {
+    let (separator, len) = (P::SEPARATOR.as_slice()[0], prefix.len());
+    prefix.push(separator);
+    let reparsed = P::prefix_len(PathStr::from_slice(prefix));
+    prefix.truncate(len);
+    if reparsed == len {
+        separator
+    } else {
+        b'\\'.into()
+    }
+}
+
+// Returns `true` if `Path::normalize` would return the path unchanged. This only
+// allocates to check a root other than `Path::SEPARATOR` after a prefix.
+fn is_lexically_normal<P: Path + ?Sized>(path: &P) -> bool {
+    let separator = P::SEPARATOR.as_slice()[0];
+    let slice = path.as_str().as_slice();
+    let prefix_len = P::prefix_len(path.as_str());
+    let mut rest = &slice[prefix_len..];
+
+    if path.has_root() {
+        let root = rest[0];
+        if root != separator
+            && (prefix_len == 0
+                || root != root_after_prefix::<P>(&mut slice[..prefix_len].to_vec()))
+        {
+            return false;
+        }
+        rest = &rest[1..];
+    }
+
+    if rest.is_empty() {
+        return true;
+    }
+
+    if rest.iter().any(|c| P::is_separator(*c) && *c != separator) {
+        return false;
+    }
+
+    // a relative path may only climb with `..` before its first normal component
+    let mut may_climb = !path.has_root();
+    for segment in rest.split(|c| *c == separator) {
+        if segment.is_empty() || segment == P::CURRENT_DIR.as_slice() {
+            return false;
+        }
+        if segment == P::PARENT_DIR.as_slice() {
+            if !may_climb {
+                return false;
+            }
+        } else {
+            may_climb = false;
+        }
+    }
+    true
+}
+
+// Splits a file name at its last dot, treating a leading dot as part of the stem
+// so that `.hidden` has no extension.
+fn rsplit_file_at_dot<P: Path + ?Sized>(file: &P::Str) -> (Option<&P::Str>, Option<&P::Str>) {
+    let dot = &P::CURRENT_DIR.as_slice()[0];
+    let slice = file.as_slice();
+
+    match slice.iter().rposition(|c| c == dot) {
+        None | Some(0) => (None, Some(file)),
+        Some(i) => (
+            Some(P::Str::from_slice(&slice[..i])),
+            Some(P::Str::from_slice(&slice[i + 1..])),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{
+        fold_ascii, Component, Path, PathOwned, PathStr, TooDeep, U16Path, U16PathBuf, U8Path,
+        U8PathBuf,
+    };
+    use alloc::borrow::Cow;
+    use bstr::{BStr, ByteSlice};
+    use widestring::{u16str, U16Str};
+
+    #[test]
+    pub fn set_extension_u8() {
+        let mut path = U8PathBuf::from("foo");
+        assert!(path.set_extension(BStr::new("txt")));
+        assert_eq!(path, U8PathBuf::from("foo.txt"));
+
+        let mut path = U8PathBuf::from("foo.tar");
+        assert!(path.set_extension(BStr::new("zip")));
+        assert_eq!(path, U8PathBuf::from("foo.zip"));
+
+        let mut path = U8PathBuf::from("/");
+        assert!(!path.set_extension(BStr::new("zip")));
+    }
+
+    #[test]
+    pub fn set_extension_u16() {
+        let mut path = U16PathBuf::from("foo");
+        assert!(path.set_extension(u16str!("txt")));
+        assert_eq!(path, U16PathBuf::from("foo.txt"));
+
+        let mut path = U16PathBuf::from("foo.tar");
+        assert!(path.set_extension(u16str!("zip")));
+        assert_eq!(path, U16PathBuf::from("foo.zip"));
+
+        let mut path = U16PathBuf::from("/");
+        assert!(!path.set_extension(u16str!("zip")));
+    }
+
+    #[test]
+    pub fn set_file_name_u8() {
+        let mut path = U8PathBuf::from("/a/b");
+        path.set_file_name(BStr::new("c"));
+        assert_eq!(path, U8PathBuf::from("/a/c"));
+    }
+
+    #[test]
+    pub fn set_file_name_u16() {
+        let mut path = U16PathBuf::from("/a/b");
+        path.set_file_name(u16str!("c"));
+        assert_eq!(path, U16PathBuf::from("/a/c"));
+
+        let mut path = U16PathBuf::from("C:a");
+        path.set_file_name(u16str!("b"));
+        assert_eq!(path.as_path().as_str(), u16str!("C:b"));
+    }
+
+    #[test]
+    pub fn with_extension_u8() {
+        let path = U8PathBuf::from("/a/b.txt");
+        assert_eq!(
+            path.with_extension(BStr::new("log")),
+            U8PathBuf::from("/a/b.log")
+        );
+        assert_eq!(path, U8PathBuf::from("/a/b.txt"));
+    }
+
+    #[test]
+    pub fn with_extension_u16() {
+        let path = U16PathBuf::from("/a/b.txt");
+        assert_eq!(
+            path.with_extension(u16str!("log")),
+            U16PathBuf::from("/a/b.log")
+        );
+        assert_eq!(path, U16PathBuf::from("/a/b.txt"));
+    }
+
+    #[test]
+    pub fn with_file_name_u8() {
+        let path = U8PathBuf::from("/a/b");
+        assert_eq!(path.with_file_name(BStr::new("c")), U8PathBuf::from("/a/c"));
+        assert_eq!(path, U8PathBuf::from("/a/b"));
+    }
+
+    #[test]
+    pub fn with_file_name_u16() {
+        let path = U16PathBuf::from("/a/b");
+        assert_eq!(path.with_file_name(u16str!("c")), U16PathBuf::from("/a/c"));
+        assert_eq!(path, U16PathBuf::from("/a/b"));
+    }
+
+    #[test]
+    pub fn is_absolute_u8() {
+        assert!(U8Path::from_str(BStr::new(r"\a")).is_absolute());
+        assert!(U8Path::from_str(BStr::new("/a")).is_absolute());
+        assert!(U8Path::from_str(BStr::new("a")).is_relative());
+        assert!(U8Path::from_str(BStr::new(r"C:\a")).is_relative());
+    }
+
+    #[test]
+    pub fn is_absolute_u16() {
+        assert!(U16Path::from_str(u16str!(r"C:\a")).is_absolute());
+        assert!(U16Path::from_str(u16str!(r"\\server\share\a")).is_absolute());
+        assert!(U16Path::from_str(u16str!(r"\a")).is_relative());
+        assert!(U16Path::from_str(u16str!("C:a")).is_relative());
+        assert!(U16Path::from_str(u16str!("a")).is_relative());
+    }
+
+    #[test]
+    pub fn default() {
+        let path: U8PathBuf = Default::default();
+        assert_eq!(path.as_path(), U8Path::empty());
+
+        let path: U16PathBuf = Default::default();
+        assert_eq!(path.as_path(), U16Path::empty());
+    }
+
+    #[test]
+    pub fn starts_with() {
+        let path = U8Path::from_str(BStr::new("/a//b/c"));
+        assert!(path.starts_with(U8Path::from_str(BStr::new("/a/b"))));
+        assert!(path.starts_with(U8Path::from_str(BStr::new("/"))));
+        assert!(!path.starts_with(U8Path::from_str(BStr::new("/a/bc"))));
+
+        let path = U16Path::from_str(u16str!("/ab"));
+        assert!(!path.starts_with(U16Path::from_str(u16str!("/a"))));
+    }
+
+    #[test]
+    pub fn from_components() {
+        let path = U8PathBuf::from("/a//b/./c/");
+        let collected = U8PathBuf::from_components(path.components());
+        assert_eq!(collected.as_path().as_str(), BStr::new("/a/b/c"));
+
+        let path = U16PathBuf::from(r"C:a\b\..");
+        let collected = U16PathBuf::from_components(path.components());
+        assert_eq!(collected.as_path().as_str(), u16str!("C:a/b/.."));
+
+        let path = U16PathBuf::from(r"C:\a");
+        let collected = U16PathBuf::from_components(path.components());
+        assert_eq!(collected.as_path().as_str(), u16str!("C:/a"));
+
+        let path = U16PathBuf::from(r"\\?\foo\a");
+        let collected = U16PathBuf::from_components(path.components());
+        assert_eq!(collected.as_path().as_str(), u16str!(r"\\?\foo\a"));
+        assert!(collected.components().eq(path.components()));
+    }
+
+    #[test]
+    pub fn normalize() {
+        let cases = [
+            ("/a/./b/../c", "/a/c"),
+            (r"/a\b//c/", "/a/b/c"),
+            ("/../a", "/a"),
+            ("../a/../../b", "../../b"),
+            ("./a", "a"),
+            ("a/..", ""),
+        ];
+        for (path, expected) in cases {
+            let path = U8Path::from_str(BStr::new(path));
+            assert_eq!(path.normalize().as_str(), BStr::new(expected));
+        }
+
+        let path = U16Path::from_str(u16str!(r"C:\a\..\b"));
+        assert_eq!(path.normalize().as_str(), u16str!("C:/b"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    pub fn normalize_cow() {
+        for path in ["/a/b/c", "a/b", "../a", "/", ""] {
+            let path = U8Path::from_str(BStr::new(path));
+            assert!(matches!(path.normalize_cow(), Cow::Borrowed(_)));
+        }
+
+        for path in ["/a/./b", "/a//b", r"/a\b", "a/", "/..", "a/../b", "."] {
+            let path = U8Path::from_str(BStr::new(path));
+            match path.normalize_cow() {
+                Cow::Owned(normal) => assert_eq!(normal, path.normalize()),
+                Cow::Borrowed(_) => panic!("{path:?} is not normal"),
+            }
+        }
+
+        let path = U16Path::from_str(u16str!("C:/a/b"));
+        assert!(matches!(path.normalize_cow(), Cow::Borrowed(_)));
+        let path = U16Path::from_str(u16str!(r"C:\a/b"));
+        assert!(matches!(path.normalize_cow(), Cow::Owned(_)));
+    }
+
+    #[test]
+    pub fn normalized() {
+        let path = U8Path::from_str(BStr::new("/a/b"));
+        assert!(
+            matches!(path.normalized(), Cow::Borrowed(borrowed) if core::ptr::eq(borrowed, path))
+        );
+
+        let path = U8Path::from_str(BStr::new(r"/a\./b/"));
+        match path.normalized() {
+            Cow::Owned(normal) => assert_eq!(normal.as_str(), "/a/b"),
+            Cow::Borrowed(_) => panic!("{path:?} is not normal"),
+        }
+
+        // the empty path is already normal, and is what `.` and `a/..` normalize to
+        assert!(matches!(U8Path::empty().normalized(), Cow::Borrowed(_)));
+        for path in [".", "a/.."] {
+            let path = U8Path::from_str(BStr::new(path));
+            assert!(matches!(path.normalized(), Cow::Owned(normal) if normal.is_empty()));
+        }
+
+        let path = U16Path::from_str(u16str!(r"C:\a\..\b"));
+        assert_eq!(path.normalized().as_str(), u16str!("C:/b"));
+    }
+
+    fn push_pop<B: PathOwned>(
+        a: &<B::Borrowed as Path>::Str,
+        b: &<B::Borrowed as Path>::Str,
+        root: &<B::Borrowed as Path>::Str,
+    ) {
+        let mut path = B::new();
+        assert!(path.as_path() == <B::Borrowed as Path>::empty());
+        assert!(!path.pop());
+
+        path.push(a);
+        path.push(b);
+        assert!(path.as_path().file_name() == Some(b));
+        assert!(path.pop());
+        assert!(path.as_path().as_str() == a);
+
+        path.push(root);
+        assert!(path.as_path() == <B::Borrowed as Path>::from_str(root));
+    }
+
+    #[test]
+    pub fn path_owned_generic() {
+        push_pop::<U8PathBuf>(BStr::new("a"), BStr::new("b"), BStr::new("/c"));
+        push_pop::<U16PathBuf>(u16str!("a"), u16str!("b"), u16str!(r"\\server\share\c"));
+    }
+
+    #[test]
+    pub fn canonical_separators() {
+        let path = U8Path::from_str(BStr::new(r"/a\b\c"));
+        assert_eq!(path.canonical_separators().as_str(), BStr::new("/a/b/c"));
+
+        let path = U16Path::from_str(u16str!(r"/a\b\.\..\c"));
+        assert_eq!(path.canonical_separators().as_str(), u16str!("/a/b/./../c"));
+
+        let path = U16Path::from_str(u16str!(r"\\?\C:\a\b"));
+        assert_eq!(path.canonical_separators().as_str(), u16str!(r"\\?\C:/a/b"));
+    }
+
+    #[test]
+    pub fn relative_to() {
+        let path = |s: &str| U8PathBuf::from(s);
+        let cases = [
+            ("/a/b/c", "/a/x", "../b/c"),
+            ("/a/b", "/a/b", ""),
+            ("/a", "/a/b/c", "../.."),
+            ("/a/./b/../c", "/a//d", "../c"),
+        ];
+        for (target, base, expected) in cases {
+            let relative = path(target).relative_to(&path(base)).unwrap();
+            assert_eq!(relative.as_str(), BStr::new(expected));
+
+            let mut joined = path(base);
+            joined.push(relative.as_str());
+            assert_eq!(joined.normalize(), path(target).normalize());
+        }
+
+        assert!(path("a/b").relative_to(&path("/a")).is_none());
+        assert!(path("/a/b").relative_to(&path("a")).is_none());
+
+        let target = U16PathBuf::from(r"C:\a\b");
+        let relative = target.relative_to(&U16PathBuf::from(r"C:\c")).unwrap();
+        assert_eq!(relative.as_str(), u16str!("../a/b"));
+        assert!(target.relative_to(&U16PathBuf::from(r"D:\c")).is_none());
+    }
+
+    #[test]
+    pub fn depth() {
+        let root = U8Path::from_str(BStr::new("/"));
+        assert_eq!(root.depth(), 0);
+        assert!(root.is_root());
+        assert!(!root.is_empty_path());
+
+        let path = U8Path::from_str(BStr::new("/a/./b/"));
+        assert_eq!(path.depth(), 2);
+        assert!(!path.is_root());
+
+        let empty = U8Path::empty();
+        assert_eq!(empty.depth(), 0);
+        assert!(empty.is_empty_path());
+        assert!(!empty.is_root());
+
+        assert!(U16Path::from_str(u16str!(r"\")).is_root());
+        assert!(!U16Path::from_str(u16str!(r"C:\")).is_root());
+        assert!(!U16Path::from_str(u16str!("C:")).is_root());
+        assert_eq!(U16Path::from_str(u16str!(r"C:\a\..")).depth(), 1);
+    }
+
+    #[test]
+    pub fn strip_extension() {
+        let path = U8Path::from_str(BStr::new("/a/b.tar.gz"));
+        let (stem, extension) = path.strip_extension();
+        assert_eq!(stem.as_str(), BStr::new("/a/b.tar"));
+        assert_eq!(extension, Some(BStr::new("gz")));
+
+        let path = U8Path::from_str(BStr::new("/a/.hidden"));
+        assert_eq!(path.strip_extension(), (path, None));
+
+        let path = U16Path::from_str(u16str!(r"C:\a\b.txt\"));
+        let (stem, extension) = path.strip_extension();
+        assert_eq!(stem.as_str(), u16str!(r"C:\a\b"));
+        assert_eq!(extension, Some(u16str!("txt")));
+
+        let path = U16Path::from_str(u16str!("/a/.hidden"));
+        assert_eq!(path.strip_extension(), (path, None));
+    }
+
+    #[test]
+    pub fn escapes_root() {
+        for path in ["..", "a/../../b", "./../a", "a/b/../../..", r"a\..\.."] {
+            let path = U8Path::from_str(BStr::new(path));
+            assert!(path.escapes_root(), "{path:?}");
+            assert!(path
+                .normalize()
+                .as_path()
+                .starts_with(U8Path::from_str(BStr::new(".."))));
+        }
+
+        for path in ["", "a", "a/b/../c", "a/..", "./a/./..", "/..", "/a/../../b"] {
+            let path = U8Path::from_str(BStr::new(path));
+            assert!(!path.escapes_root(), "{path:?}");
+        }
+
+        assert!(U16Path::from_str(u16str!("C:..")).escapes_root());
+        assert!(!U16Path::from_str(u16str!(r"C:\..")).escapes_root());
+    }
+
+    #[test]
+    pub fn join_contained() {
+        let sandbox = U8Path::from_str(BStr::new("/sandbox"));
+        let join = |other: &str| sandbox.join_contained(BStr::new(other));
+        assert_eq!(join("sub/file"), Some(U8PathBuf::from("/sandbox/sub/file")));
+        assert_eq!(join("a/../b/./c"), Some(U8PathBuf::from("/sandbox/b/c")));
+        assert_eq!(join(""), Some(U8PathBuf::from("/sandbox")));
+        assert_eq!(join("../etc/passwd"), None);
+        assert_eq!(join("a/../../sandbox2"), None);
+        assert_eq!(join("/etc/passwd"), None);
+
+        let sandbox = U16Path::from_str(u16str!(r"C:\sandbox"));
+        assert_eq!(
+            sandbox.join_contained(u16str!(r"sub\file")),
+            Some(U16PathBuf::from("C:/sandbox/sub/file"))
+        );
+        assert_eq!(sandbox.join_contained(u16str!(r"D:file")), None);
+        assert_eq!(sandbox.join_contained(u16str!(r"\file")), None);
+    }
+
+    #[test]
+    pub fn component_range() {
+        let path = U8Path::from_str(BStr::new("/a/bb/ccc"));
+        assert_eq!(
+            path.nth_component(1),
+            Some(Component::Normal(BStr::new("a")))
+        );
+        assert_eq!(path.component_range(0), Some(0..1));
+        assert_eq!(path.component_range(1), Some(1..2));
+        assert_eq!(path.component_range(2), Some(3..5));
+        assert_eq!(path.component_range(3), Some(6..9));
+        assert_eq!(path.component_range(4), None);
+
+        let path = U8Path::from_str(BStr::new("./a//../b"));
+        let ranges = (0..4).map(|n| path.component_range(n).unwrap());
+        let parts = ranges
+            .map(|range| &path.as_str()[range])
+            .collect::<Vec<_>>();
+        assert_eq!(parts, [".", "a", "..", "b"]);
+
+        let path = U16Path::from_str(u16str!(r"\\server\share\a"));
+        assert_eq!(path.component_range(0), Some(0..14));
+        assert_eq!(path.component_range(1), Some(14..15));
+        assert_eq!(path.component_range(2), Some(15..16));
+    }
+
+    #[test]
+    pub fn split_first() {
+        let mut path = U8Path::from_str(BStr::new("/a//b/c"));
+        let mut parts = Vec::new();
+        while let Some((first, rest)) = path.split_first() {
+            parts.push((first, rest.as_str()));
+            path = rest;
+        }
+        assert_eq!(
+            parts,
+            [
+                (Component::Root, BStr::new("a//b/c")),
+                (Component::Normal(BStr::new("a")), BStr::new("b/c")),
+                (Component::Normal(BStr::new("b")), BStr::new("c")),
+                (Component::Normal(BStr::new("c")), BStr::new("")),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn split_last() {
+        let mut path = U16Path::from_str(u16str!(r"C:\a\b"));
+        let mut parts = Vec::new();
+        while let Some((rest, last)) = path.split_last() {
+            parts.push((rest.as_str(), last));
+            path = rest;
+        }
+        assert_eq!(
+            parts,
+            [
+                (u16str!(r"C:\a"), Component::Normal(u16str!("b"))),
+                (u16str!(r"C:\"), Component::Normal(u16str!("a"))),
+                (u16str!("C:"), Component::Root),
+                (u16str!(""), Component::Prefix(u16str!("C:"))),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn is_parent_of() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        assert!(path("/a").is_parent_of(path("/a/b")));
+        assert!(path("/a/").is_parent_of(path("/a//b/.")));
+        assert!(path("/").is_parent_of(path("/a")));
+        assert!(!path("/a").is_parent_of(path("/a/b/c")));
+        assert!(!path("/a").is_parent_of(path("/a")));
+        assert!(!path("/a").is_parent_of(path("/ab")));
+        assert!(!path("/a").is_parent_of(path("/x/b")));
+
+        let path = |s: &'static U16Str| U16Path::from_str(s);
+        assert!(path(u16str!(r"C:\a")).is_parent_of(path(u16str!("C:/a/b"))));
+        assert!(!path(u16str!(r"C:\a")).is_parent_of(path(u16str!("D:/a/b"))));
+    }
+
+    #[test]
+    pub fn path_str_helpers() {
+        let s = BStr::new("foo/bar");
+        assert!(PathStr::starts_with(s, BStr::new("foo")));
+        assert!(PathStr::starts_with(s, BStr::new("")));
+        assert!(!PathStr::starts_with(s, BStr::new("bar")));
+        assert_eq!(
+            PathStr::split_at(s, 3),
+            (BStr::new("foo"), BStr::new("/bar"))
+        );
+        assert_eq!(PathStr::split_at(s, 0), (BStr::new(""), s));
+
+        let s = u16str!("foo/bar");
+        assert!(PathStr::starts_with(s, u16str!("foo")));
+        assert!(!PathStr::starts_with(s, u16str!("foo/bar/")));
+        assert_eq!(PathStr::split_at(s, 4), (u16str!("foo/"), u16str!("bar")));
+        assert_eq!(PathStr::split_at(s, 7), (s, u16str!("")));
+    }
+
+    #[test]
+    pub fn normalized_bytes() {
+        let a = U8Path::from_str(BStr::new("/a//b"));
+        let b = U8Path::from_str(BStr::new(r"/a/./b\"));
+        assert_eq!(a.normalized_bytes(), b"/a/b");
+        assert_eq!(a.normalized_bytes(), b.normalized_bytes());
+
+        let a = U16Path::from_str(u16str!("/a//b"));
+        let b = U16Path::from_str(u16str!(r"\a\b"));
+        assert_eq!(a, b);
+        assert_eq!(a.normalized_bytes(), b"/\0a\0/\0b\0");
+        assert_eq!(a.normalized_bytes(), b.normalized_bytes());
+    }
+
+    #[test]
+    pub fn truncate_to_depth() {
+        let mut path = U8PathBuf::from("/a/b/c/d");
+        path.truncate_to_depth(2);
+        assert_eq!(path.as_str(), "/a/b");
+
+        path.truncate_to_depth(5);
+        assert_eq!(path.as_str(), "/a/b");
+
+        path.truncate_to_depth(0);
+        assert_eq!(path.as_str(), "/");
+
+        let mut path = U8PathBuf::from("a/b");
+        path.truncate_to_depth(0);
+        assert!(path.is_empty_path());
+
+        let mut path = U16PathBuf::from(r"C:\a\b\c");
+        path.truncate_to_depth(1);
+        assert_eq!(path.as_path().as_str(), u16str!(r"C:\a"));
+        path.truncate_to_depth(0);
+        assert_eq!(path.as_path().as_str(), u16str!(r"C:\"));
+    }
+
+    #[test]
+    pub fn is_root() {
+        assert!(U8Path::root().is_root());
+        assert!(U8Path::from_str(BStr::new(r"//\")).is_root());
+        assert!(!U8Path::from_str(BStr::new("/a")).is_root());
+        assert!(!U8Path::empty().is_root());
+
+        assert!(U16Path::root().is_root());
+        assert!(U16Path::from_str(u16str!(r"\")).is_root());
+        assert!(!U16Path::from_str(u16str!(r"C:\")).is_root());
+
+        for path in ["/", r"//\", "/a", "", "."] {
+            let path = U8Path::from_str(BStr::new(path));
+            assert_eq!(path.is_the_root(), path.is_root());
+            assert_eq!(path.is_the_root(), path == U8Path::root());
+        }
+        assert!(U16Path::root().is_the_root());
+        assert!(!U16Path::from_str(u16str!(r"C:\")).is_the_root());
+    }
+
+    #[test]
+    pub fn is_empty_path() {
+        assert!(U8Path::empty().is_empty_path());
+        assert!(!U8Path::root().is_empty_path());
+        assert!(!U8Path::from_str(BStr::new(".")).is_empty_path());
+
+        assert!(U16Path::empty().is_empty_path());
+        assert!(!U16Path::root().is_empty_path());
+        assert!(!U16Path::from_str(u16str!(".")).is_empty_path());
+    }
+
+    #[test]
+    pub fn with_root() {
+        let path = U8Path::from_str(BStr::new("/a/b"));
+        assert_eq!(path.without_root().as_str(), BStr::new("a/b"));
+        assert_eq!(
+            path.without_root().without_root().as_str(),
+            BStr::new("a/b")
+        );
+        assert_eq!(
+            U8Path::from_str(BStr::new("//a")).without_root().as_str(),
+            BStr::new("a")
+        );
+        assert!(U8Path::root().without_root().is_empty_path());
+
+        let path = U8Path::from_str(BStr::new("a/b"));
+        assert_eq!(path.without_root(), path);
+        assert_eq!(path.with_root().as_str(), BStr::new("/a/b"));
+        assert_eq!(path.with_root().with_root().as_str(), BStr::new("/a/b"));
+        assert_eq!(U8Path::empty().with_root().as_str(), BStr::new("/"));
+
+        let path = U16Path::from_str(u16str!(r"C:\a"));
+        assert_eq!(path.without_root(), path);
+        assert_eq!(path.with_root().as_path(), path);
+        let path = U16Path::from_str(u16str!("C:a"));
+        assert_eq!(path.with_root().as_str(), u16str!("C:/a"));
+        let path = U16Path::from_str(u16str!(r"\a"));
+        assert_eq!(path.without_root().as_str(), u16str!("a"));
+    }
+
+    #[test]
+    pub fn common_prefix() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        let cases = [
+            ("/a/b/c", "/a/b/d", "/a/b"),
+            ("/a//b/", "/a/b", "/a//b"),
+            ("/a/b", "/a/b/c", "/a/b"),
+            ("/a/b", "/x", "/"),
+            ("a/b", "x/b", ""),
+            ("/a", "a", ""),
+            ("./a/b", "./a/c", "./a"),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(path(a).common_prefix(path(b)).as_str(), BStr::new(expected));
+        }
+
+        let path = |s: &'static U16Str| U16Path::from_str(s);
+        let shared = path(u16str!(r"C:\a\b")).common_prefix(path(u16str!("C:/a/c")));
+        assert_eq!(shared.as_str(), u16str!(r"C:\a"));
+        let shared = path(u16str!(r"C:\a")).common_prefix(path(u16str!(r"D:\a")));
+        assert!(shared.is_empty_path());
+    }
+
+    #[test]
+    pub fn is_normalized() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        for normal in ["/a/b", "a/b", "../a", "/", ""] {
+            assert!(path(normal).is_normalized(), "{normal}");
+        }
+        for not_normal in [
+            "/a/./b", "/a//b", r"/a\b", "/a/b/", "/a/../b", "/..", "a/..",
+        ] {
+            assert!(!path(not_normal).is_normalized(), "{not_normal}");
+            assert!(path(not_normal).normalize().as_path().is_normalized());
+        }
+
+        assert!(U16Path::from_str(u16str!(r"C:/a")).is_normalized());
+        assert!(!U16Path::from_str(u16str!(r"C:\a")).is_normalized());
+
+        for verbatim in [r"\\?\foo\a\..\b", r"\\?\UNC\srv\sh\a\.\b"] {
+            let normal = U16PathBuf::from(verbatim).as_path().normalize();
+            assert!(normal.as_path().is_normalized(), "{verbatim}");
+            let normalized = normal.as_path().normalized();
+            assert!(matches!(normalized, Cow::Borrowed(_)), "{verbatim}");
+        }
+    }
+
+    #[test]
+    pub fn to_owned_from_str() {
+        fn build<P: Path + ?Sized>(s: &P::Str) -> P::Owned {
+            let mut path = P::to_owned_from_str(s);
+            path.push(s);
+            path
+        }
+
+        assert_eq!(build::<U8Path>(BStr::new("a")).as_str(), BStr::new("a/a"));
+        assert_eq!(
+            build::<U16Path>(u16str!("a")).as_path().as_str(),
+            u16str!("a/a")
+        );
+    }
+
+    #[test]
+    pub fn trailing_separators() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        let cases = [
+            ("/a/b/", "/a/b", true),
+            (r"/a/b/\/", "/a/b", true),
+            ("/a/b", "/a/b", false),
+            ("/", "/", false),
+            ("//", "/", true),
+            ("a/", "a", true),
+            ("", "", false),
+        ];
+        for (p, trimmed, trailing) in cases {
+            assert_eq!(
+                path(p).trim_trailing_separators().as_str(),
+                BStr::new(trimmed)
+            );
+            assert_eq!(path(p).has_trailing_separator(), trailing, "{p}");
+        }
+
+        let path = U16Path::from_str(u16str!(r"C:\"));
+        assert_eq!(path.trim_trailing_separators(), path);
+        assert!(!path.has_trailing_separator());
+        let path = U16Path::from_str(u16str!(r"C:\a\"));
+        assert_eq!(path.trim_trailing_separators().as_str(), u16str!(r"C:\a"));
+        assert!(path.has_trailing_separator());
+    }
+
+    #[test]
+    pub fn has_extension() {
+        let path = U8Path::from_str(BStr::new("/a/file.txt"));
+        assert!(path.has_extension(BStr::new("txt"), false));
+        assert!(!path.has_extension(BStr::new("TXT"), false));
+        assert!(path.has_extension(BStr::new("TXT"), true));
+        assert!(!path.has_extension(BStr::new("tx"), true));
+        assert!(!U8Path::from_str(BStr::new("/a/file")).has_extension(BStr::new(""), true));
+        assert!(!U8Path::from_str(BStr::new("/a/.txt")).has_extension(BStr::new("txt"), false));
+
+        let path = U16Path::from_str(u16str!(r"C:\a\FILE.Txt"));
+        assert!(path.has_extension(u16str!("tXT"), true));
+        assert!(!path.has_extension(u16str!("txt"), false));
+    }
+
+    #[test]
+    pub fn pop_component() {
+        let mut path = U8PathBuf::from("/a/b//c/");
+        let popped = core::iter::from_fn(|| path.pop_component()).collect::<Vec<_>>();
+        assert_eq!(popped, ["c", "b", "a"].map(U8PathBuf::from));
+        assert_eq!(path.as_str(), "/");
+
+        let mut path = U8PathBuf::from("a/..");
+        assert_eq!(path.pop_component(), None);
+        assert_eq!(path.as_str(), "a/..");
+
+        let mut path = U16PathBuf::from(r"C:\a\b");
+        assert_eq!(path.pop_component(), Some(U16PathBuf::from("b")));
+        assert_eq!(path.pop_component(), Some(U16PathBuf::from("a")));
+        assert_eq!(path.pop_component(), None);
+        assert_eq!(path.as_path().as_str(), u16str!(r"C:\"));
+    }
+
+    #[test]
+    pub fn rebase() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        assert_eq!(
+            path("/mnt/a/file").strip_prefix(path("/mnt")),
+            Some(path("a/file"))
+        );
+        assert_eq!(
+            path("/mnt//a/").strip_prefix(path("/mnt/a")),
+            Some(path(""))
+        );
+        assert_eq!(path("/mnt/ab").strip_prefix(path("/mnt/a")), None);
+
+        let rebased = path("/mnt/a/file").rebase(path("/mnt/a"), path("/data/b"));
+        assert_eq!(rebased.unwrap().as_str(), "/data/b/file");
+        let rebased = path("/mnt/a/x/./y").rebase(path("/mnt//a"), path("rel"));
+        assert_eq!(rebased.unwrap().as_str(), "rel/x/./y");
+        assert!(path("/mnt/ab/file")
+            .rebase(path("/mnt/a"), path("/data"))
+            .is_none());
+        assert!(path("/mnt").rebase(path("/mnt/a"), path("/data")).is_none());
+
+        let rebased = path("/mnt/a/").rebase(path("/mnt/a"), path("/data/b"));
+        assert_eq!(rebased.unwrap().as_str(), "/data/b");
+
+        let path = |s: &'static U16Str| U16Path::from_str(s);
+        let rebased = path(u16str!(r"C:\a\b")).rebase(path(u16str!("C:/a")), path(u16str!(r"D:\")));
+        let rebased = rebased.unwrap();
+        assert_eq!(rebased.as_path().as_str(), u16str!(r"D:\b"));
+    }
+
+    #[test]
+    pub fn dir_components() {
+        let path = U8Path::from_str(BStr::new("/a/b/c.txt"));
+        assert_eq!(
+            path.dir_components().collect::<Vec<_>>(),
+            [
+                Component::Root,
+                Component::Normal(BStr::new("a")),
+                Component::Normal(BStr::new("b")),
+            ]
+        );
+        assert!(path
+            .dir_components()
+            .rev()
+            .eq(path.parent().unwrap().components().rev()));
+
+        let path = U8Path::from_str(BStr::new("a/.."));
+        assert_eq!(path.dir_components().count(), 2);
+        assert_eq!(
+            U8Path::root().dir_components().collect::<Vec<_>>(),
+            [Component::Root]
+        );
+
+        let path = U16Path::from_str(u16str!(r"C:\a"));
+        assert_eq!(
+            path.dir_components().collect::<Vec<_>>(),
+            [Component::Prefix(u16str!("C:")), Component::Root]
+        );
+    }
+
+    #[test]
+    pub fn is_descendant_of() {
+        let path = |s: &'static str| U8Path::from_str(BStr::new(s));
+        assert!(path("/a/b/c").is_descendant_of(path("/a")));
+        assert!(path("/a//b").is_descendant_of(path("/a/")));
+        assert!(path("/a").is_descendant_of(path("/")));
+        assert!(!path("/a").is_descendant_of(path("/a")));
+        assert!(!path("/a/./").is_descendant_of(path("/a")));
+        assert!(!path("/ab/c").is_descendant_of(path("/a")));
+        assert!(!path("/a").is_descendant_of(path("/a/b")));
+        assert!(!path("a/b").is_descendant_of(path("/a")));
+
+        let path = |s: &'static U16Str| U16Path::from_str(s);
+        assert!(path(u16str!("C:/a/b/c")).is_descendant_of(path(u16str!(r"C:\"))));
+        assert!(!path(u16str!(r"C:\a")).is_descendant_of(path(u16str!(r"C:\a"))));
+    }
+
+    #[test]
+    pub fn map_components() {
+        let path = U8Path::from_str(BStr::new("/a/./b//c"));
+        let upper = path.map_components(|name| Cow::Owned(name.to_ascii_uppercase().into()));
+        assert_eq!(upper.as_str(), "/A/B/C");
+
+        let path = U8Path::from_str(BStr::new("../a<b/c"));
+        let sanitized = path.map_components(|name| match name.contains(&b'<') {
+            true => Cow::Owned(name.replace("<", "_").into()),
+            false => Cow::Borrowed(name),
+        });
+        assert_eq!(sanitized.as_str(), "../a_b/c");
+
+        let path = U16Path::from_str(u16str!(r"C:\a\b"));
+        let mapped = path.map_components(|_| Cow::Borrowed(u16str!("x")));
+        assert_eq!(mapped.as_path().as_str(), u16str!("C:/x/x"));
+    }
+
+    #[test]
+    pub fn separator_count() {
+        assert_eq!(U8Path::from_str(BStr::new(r"/a\b/c/")).separator_count(), 4);
+        assert_eq!(U8Path::from_str(BStr::new("a")).separator_count(), 0);
+        assert_eq!(U8Path::empty().separator_count(), 0);
+        assert_eq!(U16Path::from_str(u16str!(r"/a\b/c/")).separator_count(), 4);
+        assert_eq!(U16Path::from_str(u16str!(r"\\?\C:\a")).separator_count(), 4);
+    }
+
+    #[test]
+    pub fn is_empty() {
+        fn is_empty<P: Path + ?Sized>(path: &P) -> bool {
+            path.is_empty()
+        }
+
+        assert!(is_empty(U8Path::from_str(BStr::new(""))));
+        assert!(!is_empty(U8Path::root()));
+        assert!(!is_empty(U8Path::from_str(BStr::new("."))));
+        assert!(is_empty(U16Path::empty()));
+        assert!(!is_empty(U16Path::root()));
+    }
+
+    #[test]
+    pub fn contains_nul() {
+        let path = U8Path::from_str(BStr::new(b"/a\0/b"));
+        assert!(path.contains_nul());
+        assert_eq!(path.to_c_compatible().unwrap_err().nul_position(), 2);
+
+        let path = U8Path::from_str(BStr::new("/a/b"));
+        assert!(!path.contains_nul());
+        assert_eq!(path.to_c_compatible().unwrap().as_str(), "/a/b");
+
+        let units = [b'C' as u16, b':' as u16, 0];
+        let path = U16Path::from_str(U16Str::from_slice(&units));
+        assert!(path.contains_nul());
+        assert_eq!(path.to_c_compatible().unwrap_err().nul_position(), 2);
+        assert!(!U16Path::root().contains_nul());
+        assert!(U16Path::root().to_c_compatible().is_ok());
+    }
+
+    #[test]
+    pub fn join_many() {
+        let base = U8Path::from_str(BStr::new("/base"));
+        let joined = base.join_many(["sub", "dir", "file.txt"].map(BStr::new));
+        assert_eq!(joined.as_str(), "/base/sub/dir/file.txt");
+        let joined = base.join_many(["sub", "/abs", "file.txt"].map(BStr::new));
+        assert_eq!(joined.as_str(), "/abs/file.txt");
+        assert_eq!(base.join_many([]).as_str(), "/base");
+
+        let base = U16Path::from_str(u16str!(r"C:\base"));
+        let joined = base.join_many([u16str!("a"), u16str!(r"\b"), u16str!("c")]);
+        assert_eq!(joined.as_path().as_str(), u16str!(r"C:\b/c"));
+    }
+
+    #[test]
+    pub fn root_prefix() {
+        assert_eq!(
+            U8Path::from_str(BStr::new("/a/b")).root_prefix().as_str(),
+            "/"
+        );
+        assert_eq!(
+            U8Path::from_str(BStr::new("//a")).root_prefix().as_str(),
+            "/"
+        );
+        assert!(U8Path::from_str(BStr::new("a/b")).root_prefix().is_empty());
+        assert!(U8Path::empty().root_prefix().is_empty());
+
+        let root = U16Path::from_str(u16str!(r"C:\a")).root_prefix();
+        assert_eq!(root.as_str(), u16str!(r"C:\"));
+        let root = U16Path::from_str(u16str!("C:a")).root_prefix();
+        assert_eq!(root.as_str(), u16str!("C:"));
+        let root = U16Path::from_str(u16str!(r"\\server\share\a")).root_prefix();
+        assert_eq!(root.as_str(), u16str!(r"\\server\share\"));
+        assert!(U16Path::from_str(u16str!("a")).root_prefix().is_empty());
+    }
+
+    #[test]
+    pub fn normalize_with_kind() {
+        let (path, is_dir) = U8Path::from_str(BStr::new("/a/b/")).normalize_with_kind();
+        assert_eq!(path.as_str(), "/a/b");
+        assert!(is_dir);
+
+        let (path, is_dir) = U8Path::from_str(BStr::new("/a/b")).normalize_with_kind();
+        assert_eq!(path.as_str(), "/a/b");
+        assert!(!is_dir);
+
+        let (path, is_dir) = U8Path::from_str(BStr::new("/a/../b//")).normalize_with_kind();
+        assert_eq!(path.as_str(), "/b");
+        assert!(is_dir);
+
+        let (path, is_dir) = U8Path::root().normalize_with_kind();
+        assert_eq!(path.as_str(), "/");
+        assert!(!is_dir);
+
+        let (path, is_dir) = U16Path::from_str(u16str!(r"C:\a\")).normalize_with_kind();
+        assert_eq!(path.as_path().as_str(), u16str!("C:/a"));
+        assert!(is_dir);
+    }
+
+    #[test]
+    pub fn absolutize() {
+        let base = U8Path::from_str(BStr::new("/root"));
+        let path = U8Path::from_str(BStr::new("a/b"));
+        assert_eq!(path.absolutize(base).as_str(), "/root/a/b");
+        let path = U8Path::from_str(BStr::new("../a/./b/"));
+        assert_eq!(path.absolutize(base).as_str(), "/a/b");
+        let path = U8Path::from_str(BStr::new("/x//y"));
+        assert_eq!(path.absolutize(base).as_str(), "/x/y");
+        assert_eq!(U8Path::empty().absolutize(base).as_str(), "/root");
+
+        let base = U16Path::from_str(u16str!(r"C:\root"));
+        let path = U16Path::from_str(u16str!(r"a\b"));
+        assert_eq!(
+            path.absolutize(base).as_path().as_str(),
+            u16str!("C:/root/a/b")
+        );
+        let path = U16Path::from_str(u16str!(r"\a"));
+        assert_eq!(path.absolutize(base).as_path().as_str(), u16str!("C:/a"));
+        let path = U16Path::from_str(u16str!(r"D:\a"));
+        assert_eq!(path.absolutize(base).as_path().as_str(), u16str!("D:/a"));
+    }
+
+    #[test]
+    pub fn tail_from() {
+        let path = U8Path::from_str(BStr::new("/a/b/c/d"));
+        assert_eq!(path.tail_from(0).as_str(), "a/b/c/d");
+        assert_eq!(path.tail_from(2).as_str(), "c/d");
+        assert_eq!(path.tail_from(3).as_str(), "d");
+        assert!(path.tail_from(4).is_empty());
+        assert!(path.tail_from(10).is_empty());
+
+        let path = U8Path::from_str(BStr::new("./a//./b/"));
+        assert_eq!(path.tail_from(1).as_str(), "b");
+
+        let path = U16Path::from_str(u16str!(r"C:\a\b"));
+        assert_eq!(path.tail_from(1).as_str(), u16str!("b"));
+    }
+
+    #[test]
+    pub fn dir_prefixes() {
+        let path = U8Path::from_str(BStr::new("/a/b/c"));
+        let prefixes = path.dir_prefixes().map(U8Path::as_str).collect::<Vec<_>>();
+        assert_eq!(prefixes, ["/", "/a", "/a/b"]);
+
+        let path = U8Path::from_str(BStr::new("a//b/c.txt"));
+        let prefixes = path.dir_prefixes().map(U8Path::as_str).collect::<Vec<_>>();
+        assert_eq!(prefixes, ["a", "a//b"]);
+
+        assert_eq!(U8Path::root().dir_prefixes().count(), 0);
+        assert_eq!(U8Path::empty().dir_prefixes().count(), 0);
+
+        let path = U16Path::from_str(u16str!(r"C:\a\b"));
+        let prefixes = path.dir_prefixes().map(U16Path::as_str).collect::<Vec<_>>();
+        assert_eq!(prefixes, [u16str!(r"C:\"), u16str!(r"C:\a")]);
+    }
+
+    #[test]
+    pub fn first_and_last_component() {
+        let path = U8Path::from_str(BStr::new("/a/b"));
+        assert_eq!(path.first_component(), Some(Component::Root));
+        assert_eq!(
+            path.last_component(),
+            Some(Component::Normal(BStr::new("b")))
+        );
+
+        let path = U8Path::from_str(BStr::new("../x"));
+        assert_eq!(path.first_component(), Some(Component::Parent));
+        assert_eq!(
+            path.last_component(),
+            Some(Component::Normal(BStr::new("x")))
+        );
+
+        let path = U8Path::from_str(BStr::new("a/.."));
+        assert_eq!(path.last_component(), Some(Component::Parent));
+        assert_eq!(path.file_name(), None);
+
+        assert_eq!(U8Path::root().last_component(), Some(Component::Root));
+        assert_eq!(U8Path::empty().first_component(), None);
+        assert_eq!(U8Path::empty().last_component(), None);
+
+        let path = U16Path::from_str(u16str!(r"C:\a"));
+        assert_eq!(
+            path.first_component(),
+            Some(Component::Prefix(u16str!("C:")))
+        );
+    }
+
+    #[test]
+    pub fn normalize_bounded() {
+        let path = U8Path::from_str(BStr::new("/a/./b/../c"));
+        assert_eq!(path.normalize_bounded(2).unwrap().as_str(), "/a/c");
+        assert_eq!(path.normalize_bounded(1), Err(TooDeep::new(1)));
+        assert_eq!(path.normalize_bounded(1).unwrap_err().limit(), 1);
+
+        // only what survives normalization counts
+        let path = U8Path::from_str(BStr::new("a/b/c/../../d"));
+        assert_eq!(path.normalize_bounded(2).unwrap().as_str(), "a/d");
+        let path = U8Path::from_str(BStr::new("../../a"));
+        assert_eq!(path.normalize_bounded(1).unwrap().as_str(), "../../a");
+
+        let deep = "a/".repeat(1000);
+        let path = U8Path::from_str(BStr::new(&deep));
+        assert!(path.normalize_bounded(999).is_err());
+        assert_eq!(path.normalize_bounded(1000).unwrap().depth(), 1000);
+        assert!(U8Path::empty().normalize_bounded(0).is_ok());
+    }
+
+    #[test]
+    pub fn fold_ascii_letters() {
+        assert_eq!(fold_ascii(b'A'), b'a');
+        assert_eq!(fold_ascii(b'Z'), b'z');
+        assert_eq!(fold_ascii(b'@'), b'@');
+        assert_eq!(fold_ascii(b'['), b'[');
+        assert_eq!(fold_ascii(b'a'), b'a');
+        assert_eq!(fold_ascii(u16::from(b'Q')), u16::from(b'q'));
+        assert_eq!(fold_ascii(0xC4u16), 0xC4);
+        assert_eq!(fold_ascii(0x141u16), 0x141);
+    }
+
+    #[test]
+    pub fn split_file() {
+        let path = U8Path::from_str(BStr::new("/a/b/c.txt"));
+        let (dir, name) = path.split_file();
+        assert_eq!(dir.as_str(), "/a/b");
+        assert_eq!(name, Some(BStr::new("c.txt")));
+        assert_eq!(Some(dir), path.parent());
+        assert_eq!(name, path.file_name());
+
+        let (dir, name) = U8Path::root().split_file();
+        assert_eq!(dir.as_str(), "/");
+        assert_eq!(name, None);
+
+        let (dir, name) = U8Path::from_str(BStr::new("c.txt")).split_file();
+        assert!(dir.is_empty());
+        assert_eq!(name, Some(BStr::new("c.txt")));
+
+        let (dir, name) = U8Path::from_str(BStr::new("a/..")).split_file();
+        assert_eq!(dir.as_str(), "a/..");
+        assert_eq!(name, None);
+
+        let (dir, name) = U16Path::from_str(u16str!(r"C:\a")).split_file();
+        assert_eq!(dir.as_str(), u16str!(r"C:\"));
+        assert_eq!(name, Some(u16str!("a")));
+    }
+
+    #[test]
+    pub fn separator_search_agrees() {
+        fn positions<P: Path + ?Sized>(path: &P::Str) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+            let slice = path.as_slice();
+            let naive = (0..slice.len())
+                .filter(|&i| P::is_separator(slice[i]))
+                .collect::<Vec<_>>();
+
+            let mut forward = Vec::new();
+            let mut start = 0;
+            while let Some(i) = P::find_separator(PathStr::from_slice(&slice[start..])) {
+                forward.push(start + i);
+                start += i + 1;
+            }
+
+            let mut backward = Vec::new();
+            let mut end = slice.len();
+            while let Some(i) = P::rfind_separator(PathStr::from_slice(&slice[..end])) {
+                backward.push(i);
+                end = i;
+            }
+            backward.reverse();
+            (naive, forward, backward)
+        }
+
+        for path in ["", "a", "/", r"\a/b\\c//", "a/b/", r"ü/\😀\"] {
+            let (naive, forward, backward) = positions::<U8Path>(BStr::new(path));
+            assert_eq!(forward, naive, "{path:?}");
+            assert_eq!(backward, naive, "{path:?}");
+            assert_eq!(U8Path::count_separators(BStr::new(path)), naive.len());
+
+            let (naive, forward, backward) = positions::<crate::path::PosixU8Path>(BStr::new(path));
+            assert_eq!(forward, naive, "{path:?}");
+            assert_eq!(backward, naive, "{path:?}");
+
+            let wide = widestring::U16String::from_str(path);
+            let (naive, forward, backward) = positions::<U16Path>(&wide);
+            assert_eq!(forward, naive, "{path:?}");
+            assert_eq!(backward, naive, "{path:?}");
+        }
+
+        // units whose other byte looks like a separator
+        let tricky = [0x2f00, b'/' as u16, 0x5c2f, b'\\' as u16, 0x002f, 0x2f5c];
+        let (naive, forward, backward) = positions::<U16Path>(U16Str::from_slice(&tricky));
+        assert_eq!(naive, [1, 3, 4]);
+        assert_eq!(forward, naive);
+        assert_eq!(backward, naive);
+    }
+
+    #[test]
+    pub fn is_ascii() {
+        assert!(U8Path::from_str(BStr::new("/a/B/c.txt")).is_ascii());
+        assert!(U8Path::empty().is_ascii());
+        assert!(!U8Path::from_str(BStr::new("/a/ü")).is_ascii());
+        assert!(!U8Path::from_str(BStr::new("/😀")).is_ascii());
+        assert!(!U8Path::from_str(BStr::new(b"/\x80")).is_ascii());
+
+        assert!(U16Path::from_str(u16str!(r"C:\a\B")).is_ascii());
+        assert!(!U16Path::from_str(u16str!(r"C:\ü")).is_ascii());
+        assert!(!U16Path::from_str(u16str!(r"C:\😀")).is_ascii());
+    }
 }
@@ -0,0 +1,145 @@
+//! Free functions that forward to [`Path`] methods, for callers that would rather
+//! not import the trait.
+
+use alloc::vec::Vec;
+
+use crate::path::{Component, Components, Path, PathOwned};
+
+/// Returns an iterator over the components of `path`.
+///
+/// See [`Path::components`].
+pub fn components<P: Path + ?Sized>(path: &P) -> Components<'_, P> {
+    path.components()
+}
+
+/// Returns the final normal component of `path`, if there is one.
+///
+/// See [`Path::file_name`].
+pub fn file_name<P: Path + ?Sized>(path: &P) -> Option<&P::Str> {
+    path.file_name()
+}
+
+/// Returns `path` without its final component, if there is one.
+///
+/// See [`Path::parent`].
+pub fn parent<P: Path + ?Sized>(path: &P) -> Option<&P> {
+    path.parent()
+}
+
+/// Returns a lexically normalized copy of `path`.
+///
+/// See [`Path::normalize`].
+pub fn normalize<P: Path + ?Sized>(path: &P) -> P::Owned {
+    path.normalize()
+}
+
+/// Returns a copy of `path` with `part` pushed onto it.
+///
+/// See [`PathOwned::push`].
+pub fn join<P: Path + ?Sized>(path: &P, part: &P::Str) -> P::Owned {
+    let mut path = path.to_path_buf();
+    path.push(part);
+    path
+}
+
+/// Walks the components of `a` and `b` side by side, padding the shorter path with
+/// `None` until both are used up.
+///
+/// The index of the first pair that differs is where the paths diverge. A root or
+/// prefix is counted like any other component, so `/a/b/c` and `/a/x` diverge at
+/// index 2, while `a/b/c` and `a/x` diverge at index 1.
+pub fn zip_components<'a, P: Path + ?Sized>(
+    a: &'a P,
+    b: &'a P,
+) -> impl Iterator<Item = (Option<Component<'a, P>>, Option<Component<'a, P>>)> {
+    let (mut a, mut b) = (a.components(), b.components());
+    core::iter::from_fn(move || match (a.next(), b.next()) {
+        (None, None) => None,
+        pair => Some(pair),
+    })
+}
+
+/// Sorts `paths` by their components and removes the ones equal to an earlier
+/// path, such as `/a//b` after `/a/b`.
+///
+/// The sort is stable, so of each set of equal paths, the one that came first is kept.
+pub fn dedup_paths<B: PathOwned + Ord>(paths: &mut Vec<B>) {
+    paths.sort();
+    paths.dedup();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::{U16Path, U8Path, U8PathBuf};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn forwards() {
+        let path = U8PathBuf::from("/a/./b/../c.txt");
+        let path = path.as_path();
+        assert!(components(path).eq(path.components()));
+        assert_eq!(file_name(path), Some(BStr::new("c.txt")));
+        assert_eq!(
+            parent(path).map(U8Path::as_str),
+            Some(BStr::new("/a/./b/.."))
+        );
+        assert_eq!(normalize(path).as_str(), "/a/c.txt");
+        assert_eq!(join(path, BStr::new("d")).as_str(), "/a/./b/../c.txt/d");
+
+        let path = U16Path::from_str(u16str!(r"C:\a"));
+        assert_eq!(
+            components(path).next_back(),
+            Some(Component::Normal(u16str!("a")))
+        );
+        assert_eq!(
+            join(path, u16str!("b")).as_path().as_str(),
+            u16str!(r"C:\a/b")
+        );
+    }
+
+    #[test]
+    pub fn dedup_paths() {
+        let mut paths = [
+            "/b", "/a//b", "a/b", r"/a\b/", "/a", "/a/./b", "/a/c", "/b/",
+        ]
+        .map(U8PathBuf::from)
+        .to_vec();
+        super::dedup_paths(&mut paths);
+        let paths = paths.iter().map(|path| path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["/a", "/a//b", "/a/c", "/b", "a/b"]);
+    }
+
+    #[test]
+    pub fn zip_components() {
+        let a = U8Path::from_str(BStr::new("/a/b/c"));
+        let b = U8Path::from_str(BStr::new("/a/x"));
+        let pairs = super::zip_components(a, b).collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs[3], (Some(Component::Normal(BStr::new("c"))), None));
+
+        let diverge = super::zip_components(a, b).position(|(x, y)| x != y);
+        assert_eq!(diverge, Some(2));
+        assert_eq!(
+            pairs[2],
+            (
+                Some(Component::Normal(BStr::new("b"))),
+                Some(Component::Normal(BStr::new("x")))
+            )
+        );
+
+        let a = U8Path::from_str(BStr::new("a/b/c"));
+        let b = U8Path::from_str(BStr::new("a/x"));
+        let diverge = super::zip_components(a, b).position(|(x, y)| x != y);
+        assert_eq!(diverge, Some(1));
+
+        let same = U8Path::from_str(BStr::new("/a//b/"));
+        let other = U8Path::from_str(BStr::new("/a/b"));
+        assert!(super::zip_components(same, other).all(|(x, y)| x == y));
+        assert_eq!(
+            super::zip_components(U8Path::empty(), U8Path::empty()).count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::path::components::{debug_path, hash_path, Components, State};
+use crate::path::u8path::{self, bstr_literal};
+use crate::path::{Path, PathOwned, PathStr};
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+use bstr::{BStr, BString, ByteSlice};
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use qp_trie::Break;
+
+/// An owned byte path with POSIX semantics.
+///
+/// Unlike [`U8PathBuf`](crate::path::U8PathBuf), only `/` is a separator, so a
+/// backslash is an ordinary filename byte.
+#[repr(transparent)]
+#[derive(Debug, Clone)]
+pub struct PosixU8PathBuf(BString);
+
+/// A borrowed byte path with POSIX semantics.
+///
+/// See [`PosixU8PathBuf`].
+#[repr(transparent)]
+pub struct PosixU8Path(BStr);
+
+impl From<&PosixU8Path> for PosixU8PathBuf {
+    fn from(value: &PosixU8Path) -> Self {
+        Self(BString::new(value.0.to_vec()))
+    }
+}
+
+impl Default for PosixU8PathBuf {
+    fn default() -> Self {
+        <Self as PathOwned>::new()
+    }
+}
+
+impl From<&str> for PosixU8PathBuf {
+    fn from(value: &str) -> Self {
+        Self(BString::from(value))
+    }
+}
+
+impl From<&[u8]> for PosixU8PathBuf {
+    fn from(value: &[u8]) -> Self {
+        Self(BString::from(value))
+    }
+}
+
+impl Borrow<PosixU8Path> for PosixU8PathBuf {
+    fn borrow(&self) -> &PosixU8Path {
+        unsafe {
+            // SAFETY: PosixU8Path and BStr have the same layout because repr(transparent).
+            core::mem::transmute::<&BStr, _>(self.0.borrow())
+        }
+    }
+}
+
+impl Deref for PosixU8PathBuf {
+    type Target = PosixU8Path;
+
+    fn deref(&self) -> &Self::Target {
+        self.borrow()
+    }
+}
+
+impl AsRef<PosixU8Path> for PosixU8PathBuf {
+    fn as_ref(&self) -> &PosixU8Path {
+        self.borrow()
+    }
+}
+
+impl ToOwned for PosixU8Path {
+    type Owned = PosixU8PathBuf;
+
+    fn to_owned(&self) -> Self::Owned {
+        PosixU8PathBuf::from(self)
+    }
+}
+
+/// Shows the raw path along with the components it parses into.
+impl fmt::Debug for PosixU8Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_path("PosixU8Path", self, f)
+    }
+}
+
+impl PartialEq for PosixU8Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.components() == other.components()
+    }
+}
+
+impl Eq for PosixU8Path {}
+
+impl PartialEq for PosixU8PathBuf {
+    fn eq(&self, other: &Self) -> bool {
+        // fast path for exact match
+        if self.0 == other.0 {
+            return true;
+        }
+
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for PosixU8PathBuf {}
+
+/// Hashes by components, consistent with `==`.
+impl Hash for PosixU8Path {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_path(self, state)
+    }
+}
+
+impl Hash for PosixU8PathBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state)
+    }
+}
+
+impl Path for PosixU8Path {
+    type Str = BStr;
+    type Owned = PosixU8PathBuf;
+
+    const CURRENT_DIR: &'static BStr = bstr_literal(b".");
+    const PARENT_DIR: &'static BStr = bstr_literal(b"..");
+    const SEPARATOR: &'static BStr = bstr_literal(b"/");
+
+    fn is_separator(t: <Self::Str as PathStr>::ComponentType) -> bool {
+        t == b'/'
+    }
+
+    fn find_separator(path: &Self::Str) -> Option<usize> {
+        memchr::memchr(b'/', path)
+    }
+
+    fn rfind_separator(path: &Self::Str) -> Option<usize> {
+        memchr::memrchr(b'/', path)
+    }
+
+    fn count_separators(path: &Self::Str) -> usize {
+        memchr::memchr_iter(b'/', path).count()
+    }
+
+    fn root() -> &'static Self {
+        Self::from_str(Self::SEPARATOR)
+    }
+
+    fn empty() -> &'static Self {
+        Self::from_str(bstr_literal(b""))
+    }
+
+    fn has_root(&self) -> bool {
+        self.0.first().is_some_and(|c| Self::is_separator(*c))
+    }
+
+    fn components(&self) -> Components<'_, Self> {
+        Components {
+            path: &self.0,
+            prefix_len: 0,
+            has_root: self.has_root(),
+            front: State::Prefix,
+            back: State::Body,
+            normalize: true,
+            pending_empty: false,
+        }
+    }
+
+    fn from_str(str: &Self::Str) -> &Self {
+        // SAFETY: PosixU8Path is repr(transparent) with BStr
+        unsafe { core::mem::transmute(str) }
+    }
+
+    fn as_str(&self) -> &Self::Str {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for PosixU8Path {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for PosixU8PathBuf {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Break for PosixU8PathBuf {
+    type Split = PosixU8Path;
+
+    fn empty<'a>() -> &'a Self::Split {
+        PosixU8Path::empty()
+    }
+
+    fn find_break(&self, loc: usize) -> &Self::Split {
+        PosixU8Path::from_str(u8path::find_break::<PosixU8Path>(self.0.as_bstr(), loc))
+    }
+}
+
+impl PathOwned for PosixU8PathBuf {
+    type Borrowed = PosixU8Path;
+
+    fn new() -> Self {
+        Self(BString::new(Vec::new()))
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self(BString::new(Vec::with_capacity(capacity)))
+    }
+
+    fn as_path(&self) -> &Self::Borrowed {
+        self.borrow()
+    }
+
+    fn push(&mut self, component: &<Self::Borrowed as Path>::Str) {
+        u8path::push::<PosixU8Path>(&mut self.0, component)
+    }
+
+    fn pop(&mut self) -> bool {
+        u8path::pop::<PosixU8Path>(&mut self.0)
+    }
+
+    fn make_ascii_lowercase(&mut self) {
+        self.0.make_ascii_lowercase();
+    }
+
+    fn make_lowercase(&mut self) {
+        self.0 = BString::from(self.0.to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{Component, Path, PathOwned, PosixU8Path, PosixU8PathBuf, U8PathBuf};
+    use crate::trie::PathTrie;
+    use bstr::BStr;
+
+    #[test]
+    pub fn backslash_is_literal() {
+        let path = PosixU8PathBuf::from(r"a\b");
+        assert_eq!(
+            path.components().collect::<Vec<_>>(),
+            [Component::Normal(BStr::new(r"a\b"))]
+        );
+        assert_eq!(U8PathBuf::from(r"a\b").components().count(), 2);
+
+        assert!(!PosixU8PathBuf::from(r"\a").has_root());
+        assert!(U8PathBuf::from(r"\a").has_root());
+        assert_ne!(PosixU8PathBuf::from(r"a\b"), PosixU8PathBuf::from("a/b"));
+    }
+
+    #[test]
+    pub fn push_pop() {
+        let mut path = PosixU8PathBuf::from("/a");
+        path.push(BStr::new(r"b\c"));
+        assert_eq!(path.as_str(), BStr::new(r"/a/b\c"));
+        assert_eq!(path.file_name(), Some(BStr::new(r"b\c")));
+        assert!(path.pop());
+        assert_eq!(path.as_str(), BStr::new("/a"));
+    }
+
+    #[test]
+    pub fn trie() {
+        let mut trie = PathTrie::new();
+        trie.insert(PosixU8PathBuf::from(r"/a\b"), 1);
+        trie.insert(PosixU8PathBuf::from("/a"), 2);
+
+        let lookup = PosixU8PathBuf::from(r"/a\b/c");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), BStr::new(r"/a\b"));
+
+        let lookup = PosixU8PathBuf::from(r"/a\c");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), BStr::new(""));
+
+        let mut trie = PathTrie::new();
+        trie.insert(PosixU8PathBuf::from("/a/b"), 1);
+        let lookup = PosixU8PathBuf::from("/a/bc");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), BStr::new("/a"));
+        let lookup = PosixU8PathBuf::from(r"/a/b\c");
+        assert_eq!(trie.longest_prefix(lookup).as_str(), BStr::new("/a"));
+    }
+
+    #[test]
+    pub fn separator_count() {
+        let path = PosixU8Path::from_str(BStr::new(r"/a\b/c/"));
+        assert_eq!(path.separator_count(), 3);
+    }
+}
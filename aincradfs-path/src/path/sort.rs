@@ -0,0 +1,86 @@
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::path::{fold_ascii, PathStr};
+
+/// A key that orders file names without regard to ASCII case, breaking ties by
+/// case so that the order is still total.
+///
+/// `A`, `a`, `B` and `b` sort in that order. Units are otherwise compared by value,
+/// so the order does not depend on the locale.
+///
+/// This struct is created by [`Path::sort_key`](crate::path::Path::sort_key).
+pub struct SortKey<'a, S: PathStr + ?Sized>(pub(crate) &'a S);
+
+impl<'a, S: PathStr + ?Sized> SortKey<'a, S> {
+    /// Returns the file name the key was made from.
+    pub fn name(&self) -> &'a S {
+        self.0
+    }
+}
+
+impl<'a, S: PathStr + ?Sized> Clone for SortKey<'a, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S: PathStr + ?Sized> Copy for SortKey<'a, S> {}
+
+impl<'a, S: PathStr + ?Sized + fmt::Debug> fmt::Debug for SortKey<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SortKey").field(&self.0).finish()
+    }
+}
+
+impl<'a, S: PathStr + ?Sized> PartialEq for SortKey<'a, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_slice() == other.0.as_slice()
+    }
+}
+
+impl<'a, S: PathStr + ?Sized> Eq for SortKey<'a, S> {}
+
+impl<'a, S: PathStr + ?Sized> PartialOrd for SortKey<'a, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, S: PathStr + ?Sized> Ord for SortKey<'a, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (ours, theirs) = (self.0.as_slice(), other.0.as_slice());
+        let folded = |units: &'a [S::ComponentType]| units.iter().map(|&unit| fold_ascii(unit));
+        folded(ours)
+            .cmp(folded(theirs))
+            .then_with(|| ours.cmp(theirs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{Path, U16Path, U8Path};
+    use alloc::vec::Vec;
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn sort_key() {
+        let mut names =
+            ["b", "A", "a", "B", "/x/ab", "Ab"].map(|name| U8Path::from_str(BStr::new(name)));
+        names.sort_by_key(|path| path.sort_key());
+        let sorted = names.iter().map(|path| path.as_str()).collect::<Vec<_>>();
+        assert_eq!(sorted, ["A", "a", "Ab", "/x/ab", "B", "b"]);
+
+        let mut names =
+            [u16str!(r"C:\b.TXT"), u16str!("a.txt"), u16str!("B.txt")].map(U16Path::from_str);
+        names.sort_by_key(|path| path.sort_key());
+        assert_eq!(
+            names.map(|path| path.file_name().unwrap()),
+            [u16str!("a.txt"), u16str!("B.txt"), u16str!("b.TXT"),]
+        );
+
+        let root = U8Path::root();
+        assert!(root.sort_key() < U8Path::from_str(BStr::new("a")).sort_key());
+    }
+}
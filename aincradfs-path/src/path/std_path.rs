@@ -0,0 +1,152 @@
+//! Conversions between [`std::path`] types and paths, for bridging to the host filesystem.
+
+use crate::path::{NulError, U16Path, U16PathBuf, U8Path, U8PathBuf};
+
+impl U8PathBuf {
+    /// Copies a host path into a byte path.
+    ///
+    /// On Unix this copies the raw bytes of the path. Elsewhere the path is converted
+    /// to UTF-8, replacing anything that isn't valid Unicode with `U+FFFD`.
+    pub fn from_std_path(path: &std::path::Path) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Self::from(path.as_os_str().as_bytes())
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self::from(&*path.to_string_lossy())
+        }
+    }
+}
+
+/// Fails if the path contains a NUL unit.
+///
+/// On Windows this copies the wide units of the path. Elsewhere the path is converted
+/// to UTF-16, replacing anything that isn't valid Unicode with `U+FFFD`.
+impl TryFrom<&std::path::Path> for U16PathBuf {
+    type Error = NulError;
+
+    fn try_from(path: &std::path::Path) -> Result<Self, Self::Error> {
+        #[cfg(windows)]
+        let units = {
+            use std::os::windows::ffi::OsStrExt;
+            path.as_os_str().encode_wide().collect::<Vec<_>>()
+        };
+
+        #[cfg(not(windows))]
+        let units = widestring::U16String::from_str(&path.to_string_lossy()).into_vec();
+
+        Self::try_from(units.as_slice())
+    }
+}
+
+impl U8Path {
+    /// Copies the path into a host path.
+    ///
+    /// On Unix the bytes are used as they are. Elsewhere they are decoded as UTF-8,
+    /// replacing invalid sequences with `U+FFFD`.
+    pub fn to_std_pathbuf(&self) -> std::path::PathBuf {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            std::ffi::OsString::from_vec(self.as_bytes().to_vec()).into()
+        }
+
+        #[cfg(not(unix))]
+        {
+            self.to_str_lossy().into_owned().into()
+        }
+    }
+}
+
+impl U16Path {
+    /// Copies the path into a host path.
+    ///
+    /// On Windows the wide units are used as they are. Elsewhere they are decoded as
+    /// UTF-16, replacing unpaired surrogates with `U+FFFD`.
+    pub fn to_std_pathbuf(&self) -> std::path::PathBuf {
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStringExt;
+            std::ffi::OsString::from_wide(self.as_u16_slice()).into()
+        }
+
+        #[cfg(not(windows))]
+        {
+            widestring::U16Str::from_slice(self.as_u16_slice())
+                .to_string_lossy()
+                .into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{NulError, U16PathBuf, U8PathBuf};
+
+    #[test]
+    pub fn from_std_path() {
+        let path = std::path::Path::new("/a/b/c.txt");
+        assert_eq!(
+            U8PathBuf::from_std_path(path),
+            U8PathBuf::from("/a/b/c.txt")
+        );
+        assert_eq!(
+            U16PathBuf::try_from(path),
+            Ok(U16PathBuf::from("/a/b/c.txt"))
+        );
+    }
+
+    #[test]
+    pub fn from_std_path_nul() {
+        let path = std::path::Path::new("/a\0b");
+        assert_eq!(U16PathBuf::try_from(path), Err(NulError::new(2)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn from_std_path_non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::path::Path::new(OsStr::from_bytes(b"/a/\xffb"));
+        assert_eq!(
+            U8PathBuf::from_std_path(path),
+            U8PathBuf::from(&b"/a/\xffb"[..])
+        );
+        assert_eq!(
+            U16PathBuf::try_from(path),
+            Ok(U16PathBuf::from("/a/\u{FFFD}b"))
+        );
+    }
+
+    #[test]
+    pub fn to_std_pathbuf() {
+        let path = std::path::Path::new("/a/b/c.txt");
+        assert_eq!(U8PathBuf::from_std_path(path).to_std_pathbuf(), path);
+        assert_eq!(U16PathBuf::try_from(path).unwrap().to_std_pathbuf(), path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn to_std_pathbuf_non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::path::Path::new(OsStr::from_bytes(b"/a/\xffb"));
+        assert_eq!(U8PathBuf::from_std_path(path).to_std_pathbuf(), path);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    pub fn to_std_pathbuf_unpaired_surrogate() {
+        use std::ffi::OsString;
+        use std::os::windows::ffi::OsStringExt;
+
+        let path = std::path::PathBuf::from(OsString::from_wide(&[b'a' as u16, 0xD800]));
+        let converted = U16PathBuf::try_from(path.as_path()).unwrap();
+        assert_eq!(converted.to_std_pathbuf(), path);
+    }
+}
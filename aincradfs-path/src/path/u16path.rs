@@ -1,8 +1,19 @@
-use crate::path::components::{Components, State};
-use crate::path::{Path, PathOwned, PathStr};
+use crate::path::components::{debug_path, hash_path, Components, State};
+use crate::path::{
+    AbsolutePathError, Component, NulError, Path, PathOwned, PathStr, U8Path, COMPONENT_LEN_HINT,
+};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
 use qp_trie::Break;
-use std::borrow::Borrow;
-use std::ops::Deref;
 use widestring::{u16str, U16Str, U16String};
 
 #[repr(transparent)]
@@ -10,7 +21,6 @@ use widestring::{u16str, U16Str, U16String};
 pub struct U16PathBuf(U16String);
 
 #[repr(transparent)]
-#[derive(Debug)]
 pub struct U16Path(U16Str);
 
 impl From<&U16Path> for U16PathBuf {
@@ -19,17 +29,51 @@ impl From<&U16Path> for U16PathBuf {
     }
 }
 
+impl Default for U16PathBuf {
+    fn default() -> Self {
+        <Self as PathOwned>::new()
+    }
+}
+
 impl From<&str> for U16PathBuf {
     fn from(value: &str) -> Self {
         Self(U16String::from_str(value))
     }
 }
 
+/// Fails if the slice contains a NUL unit.
+impl TryFrom<&[u16]> for U16PathBuf {
+    type Error = NulError;
+
+    fn try_from(value: &[u16]) -> Result<Self, Self::Error> {
+        match value.iter().position(|&c| c == 0) {
+            Some(position) => Err(NulError::new(position)),
+            None => Ok(Self(U16String::from_vec(value))),
+        }
+    }
+}
+
+/// Moves the vector into the path without copying.
+///
+/// Like `From<&str>` and `From<String>`, this accepts NUL units; see
+/// [`Path::to_c_compatible`] to check for them.
+impl From<Vec<u16>> for U16PathBuf {
+    fn from(value: Vec<u16>) -> Self {
+        Self(U16String::from_vec(value))
+    }
+}
+
+impl From<String> for U16PathBuf {
+    fn from(value: String) -> Self {
+        Self(U16String::from_str(&value))
+    }
+}
+
 impl Borrow<U16Path> for U16PathBuf {
     fn borrow(&self) -> &U16Path {
         unsafe {
             // SAFETY: U16Path and U16Str have the same layout because repr(transparent).
-            std::mem::transmute::<&U16Str, _>(self.0.borrow())
+            core::mem::transmute::<&U16Str, _>(self.0.borrow())
         }
     }
 }
@@ -47,6 +91,49 @@ impl AsRef<U16Path> for U16PathBuf {
         self.borrow()
     }
 }
+/// A cheap conversion to a [`U16Path`], for functions that accept anything path-like.
+///
+/// See [`AsU8Path`](crate::path::AsU8Path).
+pub trait AsU16Path {
+    fn as_u16_path(&self) -> &U16Path;
+}
+
+impl AsU16Path for U16Path {
+    fn as_u16_path(&self) -> &U16Path {
+        self
+    }
+}
+
+impl AsU16Path for U16PathBuf {
+    fn as_u16_path(&self) -> &U16Path {
+        self.borrow()
+    }
+}
+
+impl AsU16Path for U16Str {
+    fn as_u16_path(&self) -> &U16Path {
+        U16Path::from_str(self)
+    }
+}
+
+impl AsU16Path for U16String {
+    fn as_u16_path(&self) -> &U16Path {
+        U16Path::from_str(self)
+    }
+}
+
+impl AsU16Path for [u16] {
+    fn as_u16_path(&self) -> &U16Path {
+        U16Path::from_str(U16Str::from_slice(self))
+    }
+}
+
+impl<T: AsU16Path + ?Sized> AsU16Path for &T {
+    fn as_u16_path(&self) -> &U16Path {
+        (**self).as_u16_path()
+    }
+}
+
 impl ToOwned for U16Path {
     type Owned = U16PathBuf;
 
@@ -55,6 +142,161 @@ impl ToOwned for U16Path {
     }
 }
 
+impl U16PathBuf {
+    /// Creates a path from `s`, failing if it has a root or prefix.
+    ///
+    /// This is for places that only make sense relative to something else, such as
+    /// the name of an archive member.
+    pub fn new_relative(s: &str) -> Result<Self, AbsolutePathError> {
+        let path = Self::from(s);
+        if path.root_prefix().is_empty() {
+            Ok(path)
+        } else {
+            Err(AbsolutePathError(()))
+        }
+    }
+
+    /// Creates an empty path with room for `capacity` code units.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(U16String::with_capacity(capacity))
+    }
+
+    /// Returns how many code units the path can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserves room for at least `additional` more code units.
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Empties the path, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Shrinks the capacity of the path as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Consumes the path, returning its backing [`U16String`].
+    pub fn into_u16string(self) -> U16String {
+        self.0
+    }
+
+    /// Converts this buffer into a boxed [`U16Path`], dropping any excess capacity.
+    pub fn into_boxed_path(self) -> Box<U16Path> {
+        let raw = Box::into_raw(self.0.into_boxed_ustr()) as *mut U16Path;
+        // SAFETY: U16Path is repr(transparent) over U16Str.
+        unsafe { Box::from_raw(raw) }
+    }
+
+    /// Consumes the buffer, yielding each normal component as a path of its own.
+    ///
+    /// The root, any prefix, `.` and `..` are skipped.
+    pub fn into_components(self) -> impl Iterator<Item = U16PathBuf> {
+        let components = self
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(U16Path::to_owned_from_str(name)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        components.into_iter()
+    }
+}
+
+impl U16Path {
+    /// Returns the code units backing this path.
+    pub fn as_u16_slice(&self) -> &[u16] {
+        self.0.as_slice()
+    }
+
+    /// Returns the length of the path in bytes, which is twice its length in code units.
+    pub fn len_bytes(&self) -> usize {
+        self.0.len() * 2
+    }
+
+    /// Returns `true` if the path is valid UTF-16, with no unpaired surrogates.
+    pub fn is_well_formed(&self) -> bool {
+        char::decode_utf16(self.0.as_slice().iter().copied()).all(|c| c.is_ok())
+    }
+
+    /// Copies this path into a new [`Arc<U16Path>`].
+    pub fn to_arc(&self) -> Arc<U16Path> {
+        let raw = Arc::into_raw(Arc::<[u16]>::from(self.0.as_slice())) as *const U16Path;
+        // SAFETY: U16Path is repr(transparent) over U16Str, which is repr(transparent) over [u16].
+        unsafe { Arc::from_raw(raw) }
+    }
+
+    /// Copies this path into a new [`Rc<U16Path>`].
+    pub fn to_rc(&self) -> Rc<U16Path> {
+        let raw = Rc::into_raw(Rc::<[u16]>::from(self.0.as_slice())) as *const U16Path;
+        // SAFETY: U16Path is repr(transparent) over U16Str, which is repr(transparent) over [u16].
+        unsafe { Rc::from_raw(raw) }
+    }
+
+    /// Returns `true` if the path is equal to `other`, reading `other` as UTF-8.
+    ///
+    /// See [`U8Path::eq_u16`].
+    pub fn eq_u8(&self, other: &U8Path) -> bool {
+        other.eq_u16(self)
+    }
+
+    /// Returns a copy of the path with every character mapped to lower case.
+    ///
+    /// Lowercasing is Unicode-aware and may change the length of the path. Unpaired
+    /// surrogates are copied unchanged, and separators are never affected.
+    pub fn to_lowercase(&self) -> U16PathBuf {
+        U16PathBuf(to_lowercase(self.0.as_slice()))
+    }
+}
+
+impl From<Box<U16Path>> for U16PathBuf {
+    fn from(value: Box<U16Path>) -> Self {
+        let raw = Box::into_raw(value) as *mut U16Str;
+        // SAFETY: U16Path is repr(transparent) over U16Str.
+        Self(U16String::from(unsafe { Box::from_raw(raw) }))
+    }
+}
+
+/// Pushes each component in turn, as [`PathOwned::push`] does.
+///
+/// Room is reserved up front from the iterator's size hint.
+impl<'a> Extend<&'a U16Str> for U16PathBuf {
+    fn extend<I: IntoIterator<Item = &'a U16Str>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0 * (COMPONENT_LEN_HINT + 1));
+        for component in iter {
+            self.push(component);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a U16Str> for U16PathBuf {
+    fn from_iter<I: IntoIterator<Item = &'a U16Str>>(iter: I) -> Self {
+        let mut path = Self::new();
+        path.extend(iter);
+        path
+    }
+}
+
+/// Makes a path of a single component, with the root as [`Path::SEPARATOR`].
+impl From<Component<'_, U16Path>> for U16PathBuf {
+    fn from(component: Component<'_, U16Path>) -> Self {
+        U16Path::to_owned_from_str(component.as_str())
+    }
+}
+
+/// Shows the raw path along with the components it parses into.
+impl fmt::Debug for U16Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_path("U16Path", self, f)
+    }
+}
+
 impl PartialEq for U16Path {
     fn eq(&self, other: &Self) -> bool {
         // fast path for exact match
@@ -70,15 +312,87 @@ impl Eq for U16Path {}
 
 impl PartialEq for U16PathBuf {
     fn eq(&self, other: &Self) -> bool {
-        // let own_components = self.components().collect::<smallvec::SmallVec<>>();
+        // fast path for exact match
+        if self.0 == other.0 {
+            return true;
+        }
+
+        // Collecting short paths into stack buffers first is no faster (see the
+        // equality bench), and gives up the early exit on differing tails.
         self.as_ref() == other.as_ref()
     }
 }
 
 impl Eq for U16PathBuf {}
 
+impl PartialOrd for U16Path {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by components, consistent with `==`.
+impl Ord for U16Path {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.components().cmp(other.components())
+    }
+}
+
+impl PartialOrd for U16PathBuf {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U16PathBuf {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_path().cmp(other.as_path())
+    }
+}
+
+/// Hashes by components, consistent with `==`.
+impl Hash for U16Path {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_path(self, state)
+    }
+}
+
+/// Hashes the same as the borrowed [`U16Path`], so maps keyed by `U16PathBuf` can be queried
+/// with a `&U16Path`.
+impl Hash for U16PathBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state)
+    }
+}
+
+/// Compares component-wise against `other` encoded as UTF-16.
+impl PartialEq<str> for U16Path {
+    fn eq(&self, other: &str) -> bool {
+        self == U16Path::from_str(&U16String::from_str(other))
+    }
+}
+
+impl PartialEq<&str> for U16Path {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<str> for U16PathBuf {
+    fn eq(&self, other: &str) -> bool {
+        self.as_path() == other
+    }
+}
+
+impl PartialEq<&str> for U16PathBuf {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_path() == *other
+    }
+}
+
 impl Path for U16Path {
     type Str = U16Str;
+    type Owned = U16PathBuf;
 
     const CURRENT_DIR: &'static U16Str = u16str!(".");
     const PARENT_DIR: &'static U16Str = u16str!("..");
@@ -88,43 +402,156 @@ impl Path for U16Path {
         [b'/' as u16, b'\\' as u16].contains(&t)
     }
 
+    fn find_separator(path: &Self::Str) -> Option<usize> {
+        // Search the bytes for either half of a separator, then check the unit
+        // it landed in, since a non-ASCII unit can contain a separator byte.
+        let bytes: &[u8] = bytemuck::cast_slice(path.as_slice());
+        let mut start = 0;
+        while let Some(i) = memchr::memchr2(b'/', b'\\', &bytes[start..]) {
+            let unit = (start + i) / 2;
+            if Self::is_separator(path.as_slice()[unit]) {
+                return Some(unit);
+            }
+            start += i + 1;
+        }
+        None
+    }
+
+    fn rfind_separator(path: &Self::Str) -> Option<usize> {
+        let bytes: &[u8] = bytemuck::cast_slice(path.as_slice());
+        let mut end = bytes.len();
+        while let Some(i) = memchr::memrchr2(b'/', b'\\', &bytes[..end]) {
+            let unit = i / 2;
+            if Self::is_separator(path.as_slice()[unit]) {
+                return Some(unit);
+            }
+            end = i;
+        }
+        None
+    }
+
+    fn prefix_len(path: &Self::Str) -> usize {
+        parse_prefix::<Self>(path.as_slice())
+    }
+
     fn root() -> &'static Self {
-        unsafe { std::mem::transmute(Self::SEPARATOR) }
+        unsafe { core::mem::transmute(Self::SEPARATOR) }
     }
 
     fn empty() -> &'static Self {
-        const EMPTY: &'static U16Str = u16str!("");
-        unsafe { std::mem::transmute(EMPTY) }
+        const EMPTY: &U16Str = u16str!("");
+        unsafe { core::mem::transmute(EMPTY) }
     }
 
     fn has_root(&self) -> bool {
-        Self::is_separator(self.0.as_slice()[0])
+        self.0
+            .as_slice()
+            .get(Self::prefix_len(&self.0))
+            .is_some_and(|c| Self::is_separator(*c))
     }
 
-    fn components(&self) -> Components<Self> {
+    /// A Windows path is only absolute if it has both a prefix and a root.
+    ///
+    /// `\a` is relative to the drive of the current directory, and `C:a` is
+    /// relative to the current directory of drive `C:`.
+    fn is_absolute(&self) -> bool {
+        Self::prefix_len(&self.0) > 0 && self.has_root()
+    }
+
+    fn components(&self) -> Components<'_, Self> {
         Components {
             path: &self.0,
+            prefix_len: Self::prefix_len(&self.0),
             has_root: self.has_root(),
-            front: State::StartDir,
+            front: State::Prefix,
             back: State::Body,
+            normalize: true,
+            pending_empty: false,
         }
     }
 
     fn from_str(str: &Self::Str) -> &Self {
         // SAFETY: U16Path is repr(transparent) with U16Str
-        unsafe { std::mem::transmute(str) }
+        unsafe { core::mem::transmute(str) }
+    }
+
+    fn as_str(&self) -> &Self::Str {
+        &self.0
+    }
+}
+
+const BACKSLASH: u16 = b'\\' as u16;
+
+// Parses a Windows path prefix, returning its length in code units.
+//
+// Recognizes verbatim (`\\?\C:`, `\\?\UNC\server\share`, `\\?\foo`), device
+// (`\\.\COM1`), UNC (`\\server\share`) and drive (`C:`) prefixes. Verbatim
+// prefixes are only ever separated by backslashes.
+pub(crate) fn parse_prefix<P: Path<Str = U16Str> + ?Sized>(path: &[u16]) -> usize {
+    let component_end = |start: usize, verbatim: bool| {
+        path[start..]
+            .iter()
+            .position(|&c| {
+                if verbatim {
+                    c == BACKSLASH
+                } else {
+                    P::is_separator(c)
+                }
+            })
+            .map_or(path.len(), |i| start + i)
+    };
+
+    let is_drive = |path: &[u16]| {
+        matches!(path, [letter, colon, ..]
+            if *letter < 0x80 && (*letter as u8).is_ascii_alphabetic() && *colon == b':' as u16)
+    };
+
+    if path.starts_with(u16str!(r"\\?\").as_slice()) {
+        if path[4..].starts_with(u16str!(r"UNC\").as_slice()) {
+            let server = component_end(8, true);
+            if server == path.len() {
+                return server;
+            }
+            component_end(server + 1, true)
+        } else if is_drive(&path[4..]) {
+            6
+        } else {
+            component_end(4, true)
+        }
+    } else if let [a, b, dot, c, ..] = path {
+        if !P::is_separator(*a) || !P::is_separator(*b) {
+            return if is_drive(path) { 2 } else { 0 };
+        }
+        if *dot == b'.' as u16 && P::is_separator(*c) {
+            return component_end(4, false);
+        }
+
+        let server = component_end(2, false);
+        if server == 2 || server == path.len() {
+            return 0;
+        }
+        let share = component_end(server + 1, false);
+        if share == server + 1 {
+            0
+        } else {
+            share
+        }
+    } else if is_drive(path) {
+        2
+    } else {
+        0
     }
 }
 
 impl Borrow<[u8]> for U16Path {
     fn borrow(&self) -> &[u8] {
-        bytemuck::cast_slice(&self.0.as_slice())
+        bytemuck::cast_slice(self.0.as_slice())
     }
 }
 
 impl Borrow<[u8]> for U16PathBuf {
     fn borrow(&self) -> &[u8] {
-        bytemuck::cast_slice(&self.0.as_slice())
+        bytemuck::cast_slice(self.0.as_slice())
     }
 }
 
@@ -136,14 +563,7 @@ impl Break for U16PathBuf {
     }
 
     fn find_break(&self, loc: usize) -> &Self::Split {
-        let mut half_loc = loc / 2;
-
-        while !U16Path::is_separator(self.0.as_slice()[half_loc]) {
-            half_loc -= 1;
-        }
-
-        let slice = &self.0.as_slice()[..half_loc];
-        unsafe { std::mem::transmute(slice) }
+        U16Path::from_str(find_break::<U16Path>(&self.0, loc))
     }
 }
 
@@ -153,11 +573,350 @@ impl PathOwned for U16PathBuf {
         Self(U16String::new())
     }
 
-    fn push(&mut self, _component: &<Self::Borrowed as Path>::Str) {
-        todo!()
+    fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    fn as_path(&self) -> &Self::Borrowed {
+        self.borrow()
+    }
+
+    fn push(&mut self, component: &<Self::Borrowed as Path>::Str) {
+        push::<U16Path>(&mut self.0, component)
+    }
+
+    fn pop(&mut self) -> bool {
+        match self.parent().map(|parent| parent.0.len()) {
+            Some(len) => {
+                self.0.truncate(len);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn make_ascii_lowercase(&mut self) {
+        make_ascii_lowercase(self.0.as_mut_slice())
+    }
+
+    fn make_lowercase(&mut self) {
+        self.0 = to_lowercase(self.0.as_slice());
+    }
+}
+
+// The buffer operations below are shared with `CustomU16PathBuf`, which differs only
+// in which units are separators.
+
+pub(crate) fn find_break<P: Path<Str = U16Str> + ?Sized>(path: &U16Str, loc: usize) -> &U16Str {
+    let half_loc = loc / 2;
+
+    // The query ran past the end of this key, so all of it is shared. Whether the
+    // query goes on at a separator is checked by `PathTrie`, which can see it.
+    if half_loc >= path.len() {
+        return path;
+    }
+
+    // Breaks land just before a separator, which is never half of a surrogate pair,
+    // so a pair that differs only in its second unit is not split.
+    let half_loc = P::rfind_separator(&path[..=half_loc]).unwrap_or(0);
+
+    &path[..half_loc]
+}
+
+pub(crate) fn push<P: Path<Str = U16Str> + ?Sized>(buf: &mut U16String, component: &U16Str) {
+    if P::prefix_len(component) > 0 {
+        buf.clear();
+    } else if P::from_str(component).has_root() {
+        // a rooted path keeps the drive we are already on
+        buf.truncate(P::prefix_len(buf));
+    } else if buf.len() == 2 && P::prefix_len(buf) == 2 {
+        // a bare drive takes a relative component as is, since `C:a` is relative
+        // to the current directory of `C:` while `C:/a` is absolute
+    } else if buf.as_slice().last().is_some_and(|c| !P::is_separator(*c)) {
+        buf.push(P::SEPARATOR);
+    }
+    buf.push(component);
+}
+
+pub(crate) fn to_lowercase(units: &[u16]) -> U16String {
+    let mut buf = U16String::with_capacity(units.len());
+    let mut encoded = [0; 2];
+    for c in char::decode_utf16(units.iter().copied()) {
+        match c {
+            Ok(c) => {
+                for lower in c.to_lowercase() {
+                    buf.push_slice(lower.encode_utf16(&mut encoded));
+                }
+            }
+            Err(e) => buf.push_slice([e.unpaired_surrogate()]),
+        }
+    }
+    buf
+}
+
+pub(crate) fn make_ascii_lowercase(units: &mut [u16]) {
+    for c in units {
+        if *c < 0x80 {
+            *c = (*c as u8).to_ascii_lowercase() as u16;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{AsU16Path, Component, NulError, Path, PathOwned, U16Path, U16PathBuf};
+    use alloc::sync::Arc;
+    use qp_trie::Break;
+    use widestring::{u16str, U16Str};
+
+    #[test]
+    pub fn into_u16string() {
+        let path = U16PathBuf::from(r"C:\a");
+        assert_eq!(path.into_u16string(), u16str!(r"C:\a"));
+    }
+
+    #[test]
+    pub fn capacity() {
+        let mut path = U16PathBuf::with_capacity(16);
+        assert!(path.capacity() >= 16);
+
+        path.push(u16str!("a"));
+        path.reserve(64);
+        assert!(path.capacity() >= 65);
+
+        path.shrink_to_fit();
+        assert!(path.capacity() < 65);
+        assert!(path.capacity() >= 1);
+    }
+
+    #[test]
+    pub fn clear() {
+        let mut path = U16PathBuf::from("/a/b/c");
+        let capacity = path.capacity();
+        path.clear();
+        assert!(path.is_empty_path());
+        assert_eq!(path.capacity(), capacity);
+
+        path.push(u16str!("b"));
+        assert_eq!(path, U16PathBuf::from("b"));
+    }
+
+    #[test]
+    pub fn boxed_path() {
+        let mut path = U16PathBuf::from(r"C:\a");
+        path.push(u16str!("b"));
+        let boxed = path.clone().into_boxed_path();
+        assert_eq!(boxed.as_str(), u16str!(r"C:\a/b"));
+        assert_eq!(U16PathBuf::from(boxed), path);
+    }
+
+    #[test]
+    pub fn debug() {
+        let path = U16Path::from_str(u16str!(r"C:\a//b"));
+        assert_eq!(
+            format!("{path:?}"),
+            r#"U16Path { path: "C:\\a//b", components: [Prefix("C:"), Root, "a", "b"] }"#
+        );
+    }
+
+    #[test]
+    pub fn eq_str() {
+        let path = U16PathBuf::from("/a/b");
+        assert!(path == "/a/b");
+        assert!(path == "/a//b/.");
+        assert!(*path == *"/a\\b/");
+        assert!(path != "/a/c");
+        assert!(path != "a/b");
+    }
+
+    #[test]
+    pub fn as_u16_slice() {
+        let path = U16PathBuf::from("/a/\u{e9}");
+        assert_eq!(path.as_u16_slice(), u16str!("/a/\u{e9}").as_slice());
+        assert_eq!(path.len_bytes(), 8);
+    }
+
+    #[test]
+    pub fn shared_path() {
+        let path = U16PathBuf::from(r"C:\a\b");
+        let shared = path.to_arc();
+        let other = Arc::clone(&shared);
+        let handle = std::thread::spawn(move || other.file_name().map(|name| name.to_ustring()));
+        assert_eq!(handle.join().unwrap().as_deref(), Some(u16str!("b")));
+        assert_eq!(*shared, *path);
+        assert_eq!(Arc::strong_count(&shared), 1);
+        assert_eq!(*path.to_rc(), *path);
+    }
+
+    #[test]
+    pub fn try_from_slice() {
+        let path = U16PathBuf::try_from(u16str!("/a/b").as_slice()).unwrap();
+        assert_eq!(path.as_str(), u16str!("/a/b"));
+
+        let err = U16PathBuf::try_from(u16str!("/a\0/b").as_slice()).unwrap_err();
+        assert_eq!(err, NulError::new(2));
+        assert_eq!(err.nul_position(), 2);
+    }
+
+    #[test]
+    pub fn to_lowercase() {
+        let path = U16Path::from_str(u16str!(r"/Foo\BAR//ÄÖ"));
+        assert_eq!(
+            path.to_lowercase().as_path().as_str(),
+            u16str!(r"/foo\bar//äö")
+        );
+        assert_eq!(path.to_lowercase(), U16PathBuf::from("/foo/bar/äö"));
+
+        let mut units = u16str!("/A").as_slice().to_vec();
+        units.push(0xd800);
+        let path = U16Path::from_str(U16Str::from_slice(&units));
+        let mut expected = u16str!("/a").as_slice().to_vec();
+        expected.push(0xd800);
+        assert_eq!(
+            path.to_lowercase().as_path().as_str().as_slice(),
+            &expected[..]
+        );
+    }
+
+    #[test]
+    pub fn surrogate_pairs() {
+        let path = U16PathBuf::from("/a/😀b/c😁");
+        let names = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string().unwrap()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "😀b", "c😁"]);
+        assert_eq!(path.file_name().unwrap().to_string().unwrap(), "c😁");
+        assert_eq!(
+            path.parent().unwrap().as_str().to_string().unwrap(),
+            "/a/😀b"
+        );
+
+        // 😀 and 😁 share their high surrogate
+        let key = U16PathBuf::from("/a/😀/x");
+        let query = U16PathBuf::from("/a/😁/x");
+        let mismatch = key.as_u16_slice().len() - 3;
+        assert_eq!(
+            key.as_u16_slice()[..mismatch],
+            query.as_u16_slice()[..mismatch]
+        );
+        let split = key.find_break(mismatch * 2 + 1);
+        assert_eq!(split.as_str(), u16str!("/a"));
+    }
+
+    #[test]
+    pub fn from_owned() {
+        let expected = U16PathBuf::from("/a/b");
+        assert_eq!(U16PathBuf::from(String::from("/a/b")), expected);
+
+        let units = u16str!("/a/b").as_slice().to_vec();
+        let ptr = units.as_ptr();
+        let path = U16PathBuf::from(units);
+        assert_eq!(path, expected);
+        assert_eq!(path.as_u16_slice(), expected.as_u16_slice());
+        assert_eq!(path.as_u16_slice().as_ptr(), ptr);
+
+        // NUL units are accepted here and only rejected on the way out
+        let path = U16PathBuf::from(alloc::vec![b'/' as u16, 0, b'a' as u16]);
+        assert!(path.contains_nul());
+        assert_eq!(path.to_c_compatible().unwrap_err(), NulError::new(1));
+    }
+
+    #[test]
+    pub fn as_u16_path() {
+        fn depth(path: impl AsU16Path) -> usize {
+            path.as_u16_path().depth()
+        }
+
+        assert_eq!(depth(u16str!(r"C:\a\b")), 2);
+        assert_eq!(depth(u16str!("a").to_ustring()), 1);
+        assert_eq!(depth(u16str!("/a/b/c").as_slice()), 3);
+        assert_eq!(depth(U16PathBuf::from("/a")), 1);
+        let owned = U16PathBuf::from("/a/b");
+        assert_eq!(depth(&owned), 2);
+        assert_eq!(owned.depth(), 2);
+        assert_eq!(depth(U16Path::from_str(u16str!("/a/b"))), 2);
+    }
+
+    #[test]
+    pub fn hash_map_borrow() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(U16PathBuf::from(r"C:\a\\b\"), 1);
+        assert_eq!(map.get(U16Path::from_str(u16str!("C:/a/./b"))), Some(&1));
+        assert_eq!(map.get(U16Path::from_str(u16str!("C:/a"))), None);
+    }
+
+    #[test]
+    pub fn into_components() {
+        let path = U16PathBuf::from(r"C:\a\b");
+        let components = path.into_components().collect::<Vec<_>>();
+        assert_eq!(components, ["a", "b"].map(U16PathBuf::from));
+    }
+
+    #[test]
+    pub fn extend() {
+        let mut path = U16PathBuf::from(r"C:\");
+        path.extend([u16str!("a"), u16str!("b")]);
+        assert_eq!(path.as_path().as_str(), u16str!(r"C:\a/b"));
+
+        let collected: U16PathBuf = [u16str!("a"), u16str!("b")].into_iter().collect();
+        assert_eq!(collected.as_path().as_str(), u16str!("a/b"));
+    }
+
+    #[test]
+    pub fn new_relative() {
+        let path = U16PathBuf::new_relative("a/b").unwrap();
+        assert_eq!(path.as_path().as_str(), u16str!("a/b"));
+        assert!(U16PathBuf::new_relative("/a/b").is_err());
+        assert!(U16PathBuf::new_relative(r"C:\a").is_err());
+        assert!(U16PathBuf::new_relative("C:a").is_err());
+        assert!(U16PathBuf::new_relative(r"\\server\share\a").is_err());
+    }
+
+    #[test]
+    pub fn is_well_formed() {
+        assert!(U16Path::from_str(u16str!(r"C:\a\ü\😀")).is_well_formed());
+        assert!(U16Path::empty().is_well_formed());
+        let lone = U16Path::from_str(U16Str::from_slice(&[b'a' as u16, 0xD800, b'b' as u16]));
+        assert!(!lone.is_well_formed());
+        let trailing = U16Path::from_str(U16Str::from_slice(&[b'a' as u16, 0xDC00]));
+        assert!(!trailing.is_well_formed());
+    }
+
+    #[test]
+    pub fn from_component() {
+        let path = U16PathBuf::from(r"C:\a\..");
+        let parts = path.components().map(U16PathBuf::from).collect::<Vec<_>>();
+        let parts = parts
+            .iter()
+            .map(|part| part.as_path().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            [u16str!("C:"), u16str!("/"), u16str!("a"), u16str!("..")]
+        );
+        assert_eq!(
+            U16PathBuf::from(Component::Current).as_path().as_str(),
+            u16str!(".")
+        );
     }
 
-    fn pop(&mut self) {
-        todo!()
+    #[test]
+    pub fn push_after_prefix() {
+        let mut path = U16PathBuf::from("C:");
+        path.push(u16str!("a"));
+        assert_eq!(path.as_path().as_str(), u16str!("C:a"));
+        assert!(path.is_relative());
+        path.push(u16str!("b"));
+        assert_eq!(path.as_path().as_str(), u16str!("C:a/b"));
+
+        let mut path = U16PathBuf::from(r"\\srv\sh");
+        path.push(u16str!("b"));
+        assert_eq!(path.as_path().as_str(), u16str!(r"\\srv\sh/b"));
     }
 }
@@ -1,16 +1,27 @@
-use crate::path::components::{Components, State};
-use crate::path::{Path, PathOwned, PathStr};
-use bstr::{BStr, BString};
+use crate::path::components::{debug_path, hash_path, Components, State};
+use crate::path::{
+    AbsolutePathError, Component, Path, PathOwned, PathStr, U16Path, COMPONENT_LEN_HINT,
+};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use bstr::{BStr, BString, ByteSlice};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
 use qp_trie::Break;
-use std::borrow::Borrow;
-use std::ops::Deref;
+use widestring::U16Str;
 
 #[repr(transparent)]
 #[derive(Debug, Clone)]
 pub struct U8PathBuf(BString);
 
 #[repr(transparent)]
-#[derive(Debug)]
 pub struct U8Path(BStr);
 
 impl From<&U8Path> for U8PathBuf {
@@ -19,17 +30,43 @@ impl From<&U8Path> for U8PathBuf {
     }
 }
 
+impl Default for U8PathBuf {
+    fn default() -> Self {
+        <Self as PathOwned>::new()
+    }
+}
+
 impl From<&str> for U8PathBuf {
     fn from(value: &str) -> Self {
         Self(BString::from(value))
     }
 }
 
+/// Paths are arbitrary bytes, so this also provides an infallible `TryFrom<&[u8]>`
+/// to mirror the checked conversion of [`U16PathBuf`](crate::path::U16PathBuf).
+impl From<&[u8]> for U8PathBuf {
+    fn from(value: &[u8]) -> Self {
+        Self(BString::from(value))
+    }
+}
+
+impl From<Vec<u8>> for U8PathBuf {
+    fn from(value: Vec<u8>) -> Self {
+        Self(BString::from(value))
+    }
+}
+
+impl From<String> for U8PathBuf {
+    fn from(value: String) -> Self {
+        Self(BString::from(value))
+    }
+}
+
 impl Borrow<U8Path> for U8PathBuf {
     fn borrow(&self) -> &U8Path {
         unsafe {
             // SAFETY: U8Path and BStr have the same layout because repr(transparent).
-            std::mem::transmute::<&BStr, _>(self.0.borrow())
+            core::mem::transmute::<&BStr, _>(self.0.borrow())
         }
     }
 }
@@ -48,6 +85,54 @@ impl AsRef<U8Path> for U8PathBuf {
     }
 }
 
+/// A cheap conversion to a [`U8Path`], for functions that accept anything path-like
+/// in the way `std` functions take `AsRef<std::path::Path>`.
+pub trait AsU8Path {
+    fn as_u8_path(&self) -> &U8Path;
+}
+
+impl AsU8Path for U8Path {
+    fn as_u8_path(&self) -> &U8Path {
+        self
+    }
+}
+
+impl AsU8Path for U8PathBuf {
+    fn as_u8_path(&self) -> &U8Path {
+        self.borrow()
+    }
+}
+
+impl AsU8Path for BStr {
+    fn as_u8_path(&self) -> &U8Path {
+        U8Path::from_str(self)
+    }
+}
+
+impl AsU8Path for [u8] {
+    fn as_u8_path(&self) -> &U8Path {
+        U8Path::from_str(BStr::new(self))
+    }
+}
+
+impl AsU8Path for str {
+    fn as_u8_path(&self) -> &U8Path {
+        U8Path::from_str(BStr::new(self))
+    }
+}
+
+impl AsU8Path for String {
+    fn as_u8_path(&self) -> &U8Path {
+        U8Path::from_str(BStr::new(self))
+    }
+}
+
+impl<T: AsU8Path + ?Sized> AsU8Path for &T {
+    fn as_u8_path(&self) -> &U8Path {
+        (**self).as_u8_path()
+    }
+}
+
 impl ToOwned for U8Path {
     type Owned = U8PathBuf;
 
@@ -56,14 +141,237 @@ impl ToOwned for U8Path {
     }
 }
 
+impl U8PathBuf {
+    /// Creates a path from `s`, failing if it has a root or prefix.
+    ///
+    /// This is for places that only make sense relative to something else, such as
+    /// the name of an archive member.
+    pub fn new_relative(s: &str) -> Result<Self, AbsolutePathError> {
+        let path = Self::from(s);
+        if path.root_prefix().is_empty() {
+            Ok(path)
+        } else {
+            Err(AbsolutePathError(()))
+        }
+    }
+
+    /// Creates an empty path with room for `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(BString::new(Vec::with_capacity(capacity)))
+    }
+
+    /// Returns how many bytes the path can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserves room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Empties the path, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Shrinks the capacity of the path as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Consumes the path, returning its backing [`BString`].
+    pub fn into_bstring(self) -> BString {
+        self.0
+    }
+
+    /// Converts the path into a [`String`] if it is valid UTF-8, or gives it back otherwise.
+    pub fn into_string(self) -> Result<String, U8PathBuf> {
+        String::from_utf8(self.0.into()).map_err(|err| Self(err.into_bytes().into()))
+    }
+
+    /// Converts this buffer into a boxed [`U8Path`], dropping any excess capacity.
+    pub fn into_boxed_path(self) -> Box<U8Path> {
+        let raw = Box::into_raw(Vec::from(self.0).into_boxed_slice()) as *mut U8Path;
+        // SAFETY: U8Path is repr(transparent) over BStr, which is repr(transparent) over [u8].
+        unsafe { Box::from_raw(raw) }
+    }
+
+    /// Consumes the buffer, yielding each normal component as a path of its own.
+    ///
+    /// The root, any prefix, `.` and `..` are skipped.
+    pub fn into_components(self) -> impl Iterator<Item = U8PathBuf> {
+        let components = self
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(U8Path::to_owned_from_str(name)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        components.into_iter()
+    }
+}
+
+impl U8Path {
+    /// Returns the bytes backing this path.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns the bytes of the path with one leading separator removed, if it has a
+    /// root.
+    ///
+    /// `/a/b` and `a/b` both give `a/b`, and `/` gives an empty slice.
+    pub fn as_relative_bytes(&self) -> &[u8] {
+        let bytes = self.as_bytes();
+        if self.has_root() {
+            &bytes[1..]
+        } else {
+            bytes
+        }
+    }
+
+    /// Returns the length of the path in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the path as a `&str` if it is valid UTF-8.
+    pub fn to_str(&self) -> Option<&str> {
+        self.0.to_str().ok()
+    }
+
+    /// Returns `true` if the path is valid UTF-8.
+    pub fn is_well_formed(&self) -> bool {
+        self.to_str().is_some()
+    }
+
+    /// Returns the path as a string, replacing invalid UTF-8 with `U+FFFD`.
+    pub fn to_str_lossy(&self) -> Cow<'_, str> {
+        self.0.to_str_lossy()
+    }
+
+    /// Copies this path into a new [`Arc<U8Path>`].
+    pub fn to_arc(&self) -> Arc<U8Path> {
+        let raw = Arc::into_raw(Arc::<[u8]>::from(self.0.as_bytes())) as *const U8Path;
+        // SAFETY: U8Path is repr(transparent) over BStr, which is repr(transparent) over [u8].
+        unsafe { Arc::from_raw(raw) }
+    }
+
+    /// Copies this path into a new [`Rc<U8Path>`].
+    pub fn to_rc(&self) -> Rc<U8Path> {
+        let raw = Rc::into_raw(Rc::<[u8]>::from(self.0.as_bytes())) as *const U8Path;
+        // SAFETY: U8Path is repr(transparent) over BStr, which is repr(transparent) over [u8].
+        unsafe { Rc::from_raw(raw) }
+    }
+
+    /// Returns `true` if the path is equal to `other`, reading this path as UTF-8.
+    ///
+    /// Components are compared one by one, as `==` does, by decoding both sides
+    /// rather than converting either path. Invalid UTF-8 or UTF-16 in a component
+    /// makes the paths unequal.
+    pub fn eq_u16(&self, other: &U16Path) -> bool {
+        let mut ours = self.components();
+        let mut theirs = other.components();
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return true,
+                (Some(Component::Normal(a)), Some(Component::Normal(b)))
+                | (Some(Component::Prefix(a)), Some(Component::Prefix(b))) => {
+                    if !eq_transcoded(a, b) {
+                        return false;
+                    }
+                }
+                (Some(Component::Root), Some(Component::Root))
+                | (Some(Component::Current), Some(Component::Current))
+                | (Some(Component::Parent), Some(Component::Parent)) => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns a copy of the path with ASCII letters mapped to lower case.
+    ///
+    /// Separators and other bytes, including non-ASCII ones, are unchanged.
+    pub fn to_ascii_lowercase(&self) -> U8PathBuf {
+        U8PathBuf(BString::from(self.0.to_ascii_lowercase()))
+    }
+}
+
+// Compares UTF-8 with UTF-16 a character at a time.
+fn eq_transcoded(utf8: &BStr, utf16: &U16Str) -> bool {
+    let utf8 = match utf8.to_str() {
+        Ok(utf8) => utf8,
+        Err(_) => return false,
+    };
+    let mut utf16 = char::decode_utf16(utf16.as_slice().iter().copied());
+    utf8.chars()
+        .all(|c| matches!(utf16.next(), Some(Ok(d)) if c == d))
+        && utf16.next().is_none()
+}
+
+impl From<Box<U8Path>> for U8PathBuf {
+    fn from(value: Box<U8Path>) -> Self {
+        let raw = Box::into_raw(value) as *mut [u8];
+        // SAFETY: U8Path is repr(transparent) over BStr, which is repr(transparent) over [u8].
+        let bytes = unsafe { Box::from_raw(raw) };
+        Self(BString::from(bytes.into_vec()))
+    }
+}
+
+/// Pushes each component in turn, as [`PathOwned::push`] does.
+///
+/// Room is reserved up front from the iterator's size hint.
+impl<'a> Extend<&'a BStr> for U8PathBuf {
+    fn extend<I: IntoIterator<Item = &'a BStr>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0 * (COMPONENT_LEN_HINT + 1));
+        for component in iter {
+            self.push(component);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a BStr> for U8PathBuf {
+    fn from_iter<I: IntoIterator<Item = &'a BStr>>(iter: I) -> Self {
+        let mut path = Self::new();
+        path.extend(iter);
+        path
+    }
+}
+
+/// Makes a path of a single component, with the root as [`Path::SEPARATOR`].
+impl From<Component<'_, U8Path>> for U8PathBuf {
+    fn from(component: Component<'_, U8Path>) -> Self {
+        U8Path::to_owned_from_str(component.as_str())
+    }
+}
+
+// Which bytes are separators, so checking one is a single load rather than a
+// comparison per separator.
+static SEPARATORS: [bool; 256] = {
+    let mut table = [false; 256];
+    table[b'/' as usize] = true;
+    table[b'\\' as usize] = true;
+    table
+};
+
 #[inline]
-const fn bstr_literal(x: &[u8]) -> &BStr {
+pub(crate) const fn bstr_literal(x: &[u8]) -> &BStr {
     unsafe { core::mem::transmute(x) }
 }
 
+/// Shows the raw path along with the components it parses into.
+impl fmt::Debug for U8Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_path("U8Path", self, f)
+    }
+}
+
 impl PartialEq for U8Path {
     fn eq(&self, other: &Self) -> bool {
-        // let own_components = self.components().collect::<smallvec::SmallVec<>>();
+        // Collecting short paths into stack buffers first is no faster (see the
+        // equality bench), and gives up the early exit on differing tails.
         self.components() == other.components()
     }
 }
@@ -83,60 +391,152 @@ impl PartialEq for U8PathBuf {
 
 impl Eq for U8PathBuf {}
 
+impl PartialOrd for U8Path {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by components, consistent with `==`.
+impl Ord for U8Path {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.components().cmp(other.components())
+    }
+}
+
+impl PartialOrd for U8PathBuf {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U8PathBuf {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_path().cmp(other.as_path())
+    }
+}
+
+/// Hashes by components, consistent with `==`.
+impl Hash for U8Path {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_path(self, state)
+    }
+}
+
+/// Hashes the same as the borrowed [`U8Path`], so maps keyed by `U8PathBuf` can be queried
+/// with a `&U8Path`.
+impl Hash for U8PathBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state)
+    }
+}
+
+/// Compares component-wise against the UTF-8 bytes of `other`.
+impl PartialEq<str> for U8Path {
+    fn eq(&self, other: &str) -> bool {
+        self == U8Path::from_str(BStr::new(other))
+    }
+}
+
+impl PartialEq<&str> for U8Path {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<str> for U8PathBuf {
+    fn eq(&self, other: &str) -> bool {
+        self.as_path() == other
+    }
+}
+
+impl PartialEq<&str> for U8PathBuf {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_path() == *other
+    }
+}
+
 impl Path for U8Path {
     type Str = BStr;
+    type Owned = U8PathBuf;
 
     const CURRENT_DIR: &'static BStr = bstr_literal(b".");
     const PARENT_DIR: &'static BStr = bstr_literal(b"..");
     const SEPARATOR: &'static BStr = bstr_literal(b"/");
 
     fn is_separator(t: <Self::Str as PathStr>::ComponentType) -> bool {
-        [b'/', b'\\'].contains(&t)
+        SEPARATORS[t as usize]
+    }
+
+    fn find_separator(path: &Self::Str) -> Option<usize> {
+        memchr::memchr2(b'/', b'\\', path)
+    }
+
+    fn rfind_separator(path: &Self::Str) -> Option<usize> {
+        memchr::memrchr2(b'/', b'\\', path)
+    }
+
+    fn count_separators(path: &Self::Str) -> usize {
+        memchr::memchr2_iter(b'/', b'\\', path).count()
+    }
+
+    fn is_ascii(&self) -> bool {
+        self.0.is_ascii()
     }
 
     fn root() -> &'static Self {
         unsafe {
             // SAFETY: U8Path and BStr have the same layout because repr(transparent).
-            std::mem::transmute::<&BStr, _>(Self::SEPARATOR)
+            core::mem::transmute::<&BStr, _>(Self::SEPARATOR)
         }
     }
 
     fn empty() -> &'static Self {
-        const EMPTY: &'static BStr = bstr_literal(b"");
+        const EMPTY: &BStr = bstr_literal(b"");
         unsafe {
             // SAFETY: U8Path and BStr have the same layout because repr(transparent).
-            std::mem::transmute::<&BStr, _>(EMPTY)
+            core::mem::transmute::<&BStr, _>(EMPTY)
         }
     }
 
     fn has_root(&self) -> bool {
-        Self::is_separator(self.0.as_slice()[0])
+        self.0
+            .as_slice()
+            .first()
+            .is_some_and(|c| Self::is_separator(*c))
     }
 
-    fn components(&self) -> Components<Self> {
+    fn components(&self) -> Components<'_, Self> {
         Components {
             path: &self.0,
+            prefix_len: Self::prefix_len(&self.0),
             has_root: self.has_root(),
-            front: State::StartDir,
+            front: State::Prefix,
             back: State::Body,
+            normalize: true,
+            pending_empty: false,
         }
     }
 
     fn from_str(str: &Self::Str) -> &Self {
         // SAFETY: U8Path is repr(transparent) with BStr
-        unsafe { std::mem::transmute(str) }
+        unsafe { core::mem::transmute(str) }
+    }
+
+    fn as_str(&self) -> &Self::Str {
+        &self.0
     }
 }
 
 impl Borrow<[u8]> for U8Path {
     fn borrow(&self) -> &[u8] {
-        &self.0.as_slice()
+        self.0.as_slice()
     }
 }
 
 impl Borrow<[u8]> for U8PathBuf {
     fn borrow(&self) -> &[u8] {
-        &self.0.as_slice()
+        self.0.as_slice()
     }
 }
 
@@ -147,13 +547,8 @@ impl Break for U8PathBuf {
         U8Path::empty()
     }
 
-    fn find_break(&self, mut loc: usize) -> &Self::Split {
-        while !U8Path::is_separator(self.0[loc]) {
-            loc -= 1;
-        }
-
-        // SAFETY: BStr has the same layout as [] as U8Path
-        unsafe { std::mem::transmute(&self.0.as_slice()[..loc]) }
+    fn find_break(&self, loc: usize) -> &Self::Split {
+        U8Path::from_str(find_break::<U8Path>(self.0.as_bstr(), loc))
     }
 }
 
@@ -164,11 +559,375 @@ impl PathOwned for U8PathBuf {
         Self(BString::new(Vec::new()))
     }
 
-    fn push(&mut self, _component: &<Self::Borrowed as Path>::Str) {
-        todo!()
+    fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+
+    fn as_path(&self) -> &Self::Borrowed {
+        self.borrow()
+    }
+
+    fn push(&mut self, component: &<Self::Borrowed as Path>::Str) {
+        push::<U8Path>(&mut self.0, component)
     }
 
-    fn pop(&mut self) {
-        todo!()
+    fn pop(&mut self) -> bool {
+        pop::<U8Path>(&mut self.0)
+    }
+
+    fn make_ascii_lowercase(&mut self) {
+        self.0.make_ascii_lowercase();
+    }
+
+    fn make_lowercase(&mut self) {
+        self.0 = BString::from(self.0.to_lowercase());
+    }
+}
+
+// The buffer operations below are shared with `PosixU8PathBuf`, which differs only
+// in which bytes are separators.
+
+pub(crate) fn find_break<P: Path<Str = BStr> + ?Sized>(path: &BStr, loc: usize) -> &BStr {
+    // The query ran past the end of this key, so all of it is shared. Whether the
+    // query goes on at a separator is checked by `PathTrie`, which can see it.
+    if loc >= path.len() {
+        return path;
+    }
+
+    let loc = P::rfind_separator(path[..=loc].as_bstr()).unwrap_or(0);
+    path[..loc].as_bstr()
+}
+
+pub(crate) fn push<P: Path<Str = BStr> + ?Sized>(buf: &mut BString, component: &BStr) {
+    if P::from_str(component).has_root() {
+        buf.clear();
+    } else if buf.last().is_some_and(|c| !P::is_separator(*c)) {
+        buf.extend_from_slice(P::SEPARATOR);
+    }
+    buf.extend_from_slice(component);
+}
+
+pub(crate) fn pop<P: Path<Str = BStr> + ?Sized>(buf: &mut BString) -> bool {
+    match P::from_str(buf.as_bstr()).parent() {
+        Some(parent) => {
+            let len = parent.as_str().len();
+            buf.truncate(len);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{AsU8Path, Component, Path, PathOwned, U16Path, U8Path, U8PathBuf};
+    use alloc::sync::Arc;
+    use bstr::BStr;
+    use widestring::{u16str, U16Str};
+
+    #[test]
+    pub fn debug() {
+        let path = U8Path::from_str(BStr::new("/a//b"));
+        assert_eq!(
+            format!("{path:?}"),
+            r#"U8Path { path: "/a//b", components: [Root, "a", "b"] }"#
+        );
+    }
+
+    #[test]
+    pub fn eq_str() {
+        let path = U8PathBuf::from("/a/b");
+        assert!(path == "/a/b");
+        assert!(path == "/a//b/.");
+        assert!(*path == *"/a\\b/");
+        assert!(path != "/a/c");
+        assert!(path != "a/b");
+    }
+
+    #[test]
+    pub fn as_bytes() {
+        let path = U8PathBuf::from("/a/\u{e9}");
+        assert_eq!(path.as_bytes(), "/a/\u{e9}".as_bytes());
+        assert_eq!(path.len_bytes(), 5);
+    }
+
+    #[test]
+    pub fn to_str() {
+        let path = U8PathBuf::from("/a/b");
+        assert_eq!(path.to_str(), Some("/a/b"));
+        assert_eq!(path.to_str_lossy(), "/a/b");
+
+        let path = U8PathBuf::from(&b"/a/\xffb"[..]);
+        assert_eq!(path.to_str(), None);
+        assert_eq!(path.to_str_lossy(), "/a/\u{FFFD}b");
+    }
+
+    #[test]
+    pub fn into_string() {
+        let path = U8PathBuf::from("/a/b");
+        assert_eq!(path.clone().into_bstring(), "/a/b");
+        assert_eq!(path.into_string(), Ok("/a/b".to_string()));
+
+        let path = U8PathBuf::from(&b"/a/\xffb"[..]);
+        assert_eq!(path.clone().into_string(), Err(path));
+    }
+
+    #[test]
+    pub fn capacity() {
+        let mut path = U8PathBuf::with_capacity(16);
+        assert!(path.capacity() >= 16);
+
+        path.push(BStr::new("a"));
+        path.reserve(64);
+        assert!(path.capacity() >= 65);
+
+        path.shrink_to_fit();
+        assert!(path.capacity() < 65);
+        assert!(path.capacity() >= 1);
+    }
+
+    #[test]
+    pub fn clear() {
+        let mut path = U8PathBuf::from("/a/b/c");
+        let capacity = path.capacity();
+        path.clear();
+        assert!(path.is_empty_path());
+        assert_eq!(path.capacity(), capacity);
+
+        path.push(BStr::new("b"));
+        assert_eq!(path, U8PathBuf::from("b"));
+    }
+
+    #[test]
+    pub fn boxed_path() {
+        let mut path = U8PathBuf::from("/a/b");
+        path.push("c".into());
+        let boxed = path.clone().into_boxed_path();
+        assert_eq!(boxed.as_str(), "/a/b/c");
+        assert_eq!(U8PathBuf::from(boxed), path);
+    }
+
+    #[test]
+    pub fn shared_path() {
+        let path = U8PathBuf::from("/a/b");
+        let shared = path.to_arc();
+        let other = Arc::clone(&shared);
+        let handle = std::thread::spawn(move || other.file_name().map(|name| name.to_vec()));
+        assert_eq!(handle.join().unwrap().as_deref(), Some(&b"b"[..]));
+        assert_eq!(*shared, *path);
+        assert_eq!(Arc::strong_count(&shared), 1);
+        assert_eq!(*path.to_rc(), *path);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_fallible_conversions)]
+    pub fn try_from_slice() {
+        let path = U8PathBuf::try_from(&b"/a/\xffb"[..]).unwrap();
+        assert_eq!(path.as_str(), BStr::new(b"/a/\xffb"));
+    }
+
+    #[test]
+    pub fn to_ascii_lowercase() {
+        let path = U8Path::from_str(BStr::new(r"/Foo\BAR//Ünï"));
+        assert_eq!(path.to_ascii_lowercase().as_str(), r"/foo\bar//Ünï");
+        assert_eq!(path.to_ascii_lowercase(), U8PathBuf::from("/foo/bar/Ünï"));
+    }
+
+    #[test]
+    pub fn from_owned() {
+        let expected = U8PathBuf::from("/a/b");
+        assert_eq!(
+            U8PathBuf::from(b"/a/b".to_vec()).as_str(),
+            expected.as_str()
+        );
+        assert_eq!(
+            U8PathBuf::from(String::from("/a/b")).as_str(),
+            expected.as_str()
+        );
+        assert_eq!(U8PathBuf::from(&b"/a/b"[..]).as_str(), expected.as_str());
+
+        let bytes = b"/a/b".to_vec();
+        let ptr = bytes.as_ptr();
+        assert_eq!(U8PathBuf::from(bytes).as_bytes().as_ptr(), ptr);
+    }
+
+    #[test]
+    pub fn as_u8_path() {
+        fn depth(path: impl AsU8Path) -> usize {
+            path.as_u8_path().depth()
+        }
+
+        assert_eq!(depth("/a/b"), 2);
+        assert_eq!(depth(String::from("/a/b/c")), 3);
+        assert_eq!(depth(&b"a"[..]), 1);
+        assert_eq!(depth(BStr::new("a/b")), 2);
+        assert_eq!(depth(U8PathBuf::from("/a")), 1);
+        let owned = U8PathBuf::from("/a/b");
+        assert_eq!(depth(&owned), 2);
+        assert_eq!(owned.depth(), 2);
+        assert_eq!(depth(U8Path::from_str(BStr::new("/a/b"))), 2);
+    }
+
+    #[test]
+    pub fn eq_u16() {
+        let path = U8Path::from_str(BStr::new("/a//b/./c"));
+        assert!(path.eq_u16(U16Path::from_str(u16str!(r"\a\b\c"))));
+        assert!(U16Path::from_str(u16str!("/a/b/c")).eq_u8(path));
+        assert!(!path.eq_u16(U16Path::from_str(u16str!("/a/b"))));
+        assert!(!path.eq_u16(U16Path::from_str(u16str!("/a/b/d"))));
+        assert!(!path.eq_u16(U16Path::from_str(u16str!("a/b/c"))));
+
+        let path = U8Path::from_str(BStr::new("/dïr/😀"));
+        assert!(path.eq_u16(U16Path::from_str(u16str!("/dïr/😀"))));
+        assert!(!path.eq_u16(U16Path::from_str(u16str!("/dir/😀"))));
+
+        let path = U8Path::from_str(BStr::new(b"/a\xff"));
+        assert!(!path.eq_u16(U16Path::from_str(u16str!("/a\u{fffd}"))));
+        let units = [b'/' as u16, 0xd800];
+        let unpaired = U16Path::from_str(U16Str::from_slice(&units));
+        assert!(!U8Path::from_str(BStr::new("/\u{fffd}")).eq_u16(unpaired));
+    }
+
+    #[test]
+    pub fn hash_map_borrow() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(U8PathBuf::from("/a//b/./c/"), 1);
+        assert_eq!(map.get(U8Path::from_str(BStr::new(r"/a\b/c"))), Some(&1));
+        assert_eq!(map.get(U8Path::from_str(BStr::new("/a/b/c"))), Some(&1));
+        assert_eq!(map.get(U8Path::from_str(BStr::new("/a/b"))), None);
+    }
+
+    #[test]
+    pub fn is_separator() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(U8Path::is_separator(byte), [b'/', b'\\'].contains(&byte));
+        }
+    }
+
+    #[test]
+    pub fn into_components() {
+        let path = U8PathBuf::from("/a//b/./c/");
+        let components = path.into_components().collect::<Vec<_>>();
+        assert_eq!(components, ["a", "b", "c"].map(U8PathBuf::from));
+        assert_eq!(U8PathBuf::from("../a/..").into_components().count(), 1);
+    }
+
+    #[test]
+    pub fn extend() {
+        let names = ["mnt", "a", "b"].map(BStr::new);
+        let mut pushed = U8PathBuf::from("/");
+        for name in names {
+            pushed.push(name);
+        }
+
+        let mut extended = U8PathBuf::from("/");
+        extended.extend(names);
+        assert_eq!(extended, pushed);
+        assert!(extended.capacity() >= names.len() * (super::COMPONENT_LEN_HINT + 1));
+
+        let collected: U8PathBuf = [BStr::new("/"), BStr::new("mnt")]
+            .into_iter()
+            .chain(names[1..].iter().copied())
+            .collect();
+        assert_eq!(collected, pushed);
+        assert_eq!(
+            U8PathBuf::from_iter([BStr::new("a"), BStr::new("/b")]),
+            "/b"
+        );
+    }
+
+    #[test]
+    pub fn new_relative() {
+        assert_eq!(U8PathBuf::new_relative("a/b").unwrap(), "a/b");
+        assert_eq!(U8PathBuf::new_relative("").unwrap(), "");
+        assert!(U8PathBuf::new_relative("/a/b").is_err());
+        assert!(U8PathBuf::new_relative(r"\a").is_err());
+    }
+
+    #[test]
+    pub fn is_well_formed() {
+        assert!(U8Path::from_str(BStr::new("/a/ü/b")).is_well_formed());
+        assert!(U8Path::empty().is_well_formed());
+        assert!(!U8Path::from_str(BStr::new(b"/a/\xff/b")).is_well_formed());
+        // a truncated sequence
+        assert!(!U8Path::from_str(BStr::new(b"/a\xc3")).is_well_formed());
+    }
+
+    #[test]
+    pub fn from_component() {
+        let path = U8PathBuf::from("/a/..");
+        let parts = path.components().map(U8PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].as_str(), "/");
+        assert_eq!(parts[1].as_str(), "a");
+        assert_eq!(parts[2].as_str(), "..");
+        assert_eq!(U8PathBuf::from(Component::Current).as_str(), ".");
+        assert!(parts[0].has_root());
+    }
+
+    #[test]
+    pub fn as_relative_bytes() {
+        assert_eq!(
+            U8Path::from_str(BStr::new("/a/b")).as_relative_bytes(),
+            b"a/b"
+        );
+        assert_eq!(
+            U8Path::from_str(BStr::new(r"\a/b")).as_relative_bytes(),
+            b"a/b"
+        );
+        assert_eq!(
+            U8Path::from_str(BStr::new("a/b")).as_relative_bytes(),
+            b"a/b"
+        );
+        assert_eq!(U8Path::root().as_relative_bytes(), b"");
+        assert_eq!(U8Path::empty().as_relative_bytes(), b"");
+        // only the one separator that makes the path rooted is removed
+        assert_eq!(
+            U8Path::from_str(BStr::new("//a")).as_relative_bytes(),
+            b"/a"
+        );
+    }
+
+    #[test]
+    pub fn ord() {
+        let path = |path| U8PathBuf::from(path);
+        assert_eq!(
+            path("/a//b").cmp(&path(r"/a\b/")),
+            core::cmp::Ordering::Equal
+        );
+        assert!(path("/a") < path("/a/b"));
+        assert!(path("/a/b") < path("/a/c"));
+        assert!(path("/a/b") < path("/ab"));
+        assert!(path("/z") < path("a"));
+        assert!(path("..") < path("a"));
+
+        let mut paths = [path("b"), path("/a/b"), path("/a")];
+        paths.sort();
+        assert_eq!(paths, [path("/a"), path("/a/b"), path("b")]);
+    }
+
+    #[test]
+    pub fn eq_matches_collected_components() {
+        let parts = ["a", "b", ".", "..", "/", "\\", "//"];
+        let mut paths = vec![String::new()];
+        for _ in 0..3 {
+            let longer = paths
+                .iter()
+                .flat_map(|path| parts.iter().map(move |part| format!("{path}{part}")))
+                .collect::<Vec<_>>();
+            paths.extend(longer);
+        }
+
+        for a in &paths {
+            let a = U8Path::from_str(BStr::new(a));
+            let ours = a.components().collect::<Vec<_>>();
+            for b in &paths {
+                let b = U8Path::from_str(BStr::new(b));
+                let theirs = b.components().collect::<Vec<_>>();
+                assert_eq!(a == b, ours == theirs, "{a:?} {b:?}");
+            }
+        }
     }
 }
@@ -0,0 +1,107 @@
+use crate::path::{Component, InvalidName, Path, PathStr};
+
+const RESERVED: [&[u8]; 4] = [b"CON", b"PRN", b"AUX", b"NUL"];
+const RESERVED_NUMBERED: [&[u8]; 2] = [b"COM", b"LPT"];
+const ILLEGAL: &[u8] = b"<>:\"|?*";
+
+// Compares a run of units against ASCII, ignoring case.
+fn eq_ignore_ascii_case<T: Copy + PartialEq + From<u8>>(units: &[T], ascii: &[u8]) -> bool {
+    units.len() == ascii.len()
+        && units.iter().zip(ascii).all(|(&unit, &c)| {
+            unit == T::from(c.to_ascii_uppercase()) || unit == T::from(c.to_ascii_lowercase())
+        })
+}
+
+// Device names are reserved with any extension, so `CON.txt` is rejected as well.
+fn is_reserved<T: Copy + PartialEq + From<u8>>(name: &[T]) -> bool {
+    let stem = match name.iter().position(|&unit| unit == T::from(b'.')) {
+        Some(i) => &name[..i],
+        None => name,
+    };
+
+    if RESERVED
+        .iter()
+        .any(|reserved| eq_ignore_ascii_case(stem, reserved))
+    {
+        return true;
+    }
+    match stem.split_last() {
+        Some((&digit, base)) => {
+            (b'1'..=b'9').any(|d| digit == T::from(d))
+                && RESERVED_NUMBERED
+                    .iter()
+                    .any(|reserved| eq_ignore_ascii_case(base, reserved))
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn validate_component<P: Path + ?Sized>(name: &P::Str) -> Result<(), InvalidName> {
+    let name = name.as_slice();
+    if let Some(position) = name
+        .iter()
+        .position(|&unit| ILLEGAL.iter().any(|&c| unit == c.into()))
+    {
+        return Err(InvalidName::IllegalCharacter { position });
+    }
+    if matches!(name.last(), Some(&unit) if unit == b'.'.into() || unit == b' '.into()) {
+        return Err(InvalidName::TrailingDotOrSpace);
+    }
+    if is_reserved(name) {
+        return Err(InvalidName::Reserved);
+    }
+    Ok(())
+}
+
+pub(crate) fn validate<P: Path + ?Sized>(path: &P) -> Result<(), InvalidName> {
+    path.components().try_for_each(|component| match component {
+        Component::Normal(name) => validate_component::<P>(name),
+        _ => Ok(()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::{InvalidName, Path, U16Path, U8Path};
+    use bstr::BStr;
+    use widestring::u16str;
+
+    #[test]
+    pub fn validate_component() {
+        let validate = |name: &str| U8Path::validate_component(BStr::new(name));
+        assert_eq!(validate("foo.txt"), Ok(()));
+        assert_eq!(validate("console"), Ok(()));
+        assert_eq!(validate("COM0"), Ok(()));
+        assert_eq!(validate("CON"), Err(InvalidName::Reserved));
+        assert_eq!(validate("con.txt"), Err(InvalidName::Reserved));
+        assert_eq!(validate("Lpt9"), Err(InvalidName::Reserved));
+        assert_eq!(validate("a "), Err(InvalidName::TrailingDotOrSpace));
+        assert_eq!(validate("a."), Err(InvalidName::TrailingDotOrSpace));
+        assert_eq!(
+            validate("a<b"),
+            Err(InvalidName::IllegalCharacter { position: 1 })
+        );
+
+        assert_eq!(U16Path::validate_component(u16str!("foo.txt")), Ok(()));
+        assert_eq!(
+            U16Path::validate_component(u16str!("nul")),
+            Err(InvalidName::Reserved)
+        );
+        assert_eq!(
+            U16Path::validate_component(u16str!("a?")),
+            Err(InvalidName::IllegalCharacter { position: 1 })
+        );
+    }
+
+    #[test]
+    pub fn validate() {
+        let path = U16Path::from_str(u16str!(r"C:\dir\..\foo.txt"));
+        assert_eq!(path.validate(), Ok(()));
+
+        let path = U16Path::from_str(u16str!(r"C:\dir\aux\foo.txt"));
+        assert_eq!(path.validate(), Err(InvalidName::Reserved));
+
+        let path = U8Path::from_str(BStr::new("/a/b /c"));
+        assert_eq!(path.validate(), Err(InvalidName::TrailingDotOrSpace));
+    }
+}
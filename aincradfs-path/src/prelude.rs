@@ -0,0 +1,15 @@
+//! The path types and free functions most callers need, in one import.
+//!
+//! ```
+//! use aincradfs_path::prelude::*;
+//!
+//! let base = U8PathBuf::from("/guest/./data/../saves");
+//! let base = normalize(&*base);
+//! let file = join(&*base, "slot1.sav".into());
+//! assert_eq!(file, "/guest/saves/slot1.sav");
+//! ```
+
+pub use crate::path::ops::{
+    components, dedup_paths, file_name, join, normalize, parent, zip_components,
+};
+pub use crate::path::{Component, U16Path, U16PathBuf, U8Path, U8PathBuf};
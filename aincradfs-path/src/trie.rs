@@ -1,31 +1,755 @@
-use crate::path::PathOwned;
+use crate::path::{Component, Path, PathOwned, PathStr};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::error::Error;
+use core::fmt;
 use qp_trie::Break;
-use std::borrow::Borrow;
 
-#[derive(Debug)]
-pub struct PathTrie<K: PathOwned, V>(qp_trie::Trie<K, V>);
+/// A map from paths to values, supporting longest-prefix lookups.
+///
+/// Keys are stored in the form [`PathOwned::from_components`] gives them, and
+/// queries are normalized the same way, so paths that compare equal (such as
+/// `/a//b` and `/a/b`) always find the same entry.
+///
+/// Keys are compared case-sensitively unless the trie is made with
+/// [`with_case_policy`](Self::with_case_policy).
+///
+/// Cloning copies every node; `qp_trie` has no way to share structure between
+/// tries.
+#[derive(Clone, Debug)]
+pub struct PathTrie<K: PathOwned, V> {
+    trie: qp_trie::Trie<K, V>,
+    policy: CasePolicy,
+    // the spelling each folded key was first inserted with; empty if nothing is folded
+    originals: qp_trie::Trie<K, K>,
+}
+
+// Rebuilds a key from its components, collapsing repeated separators and `.`
+// and using only the canonical separator.
+fn normalize_key<K: PathOwned>(path: &K) -> K {
+    K::from_components(path.as_path().components())
+}
+
+// Returns `true` if `path` is already in the form `normalize_key` gives, by walking
+// its components the way `PathOwned::from_components` joins them.
+fn is_key_form<P: Path + ?Sized>(path: &P) -> bool {
+    let slice = path.as_str().as_slice();
+    let separator = P::SEPARATOR.as_slice();
+    let mut rest = slice;
+    let mut needs_separator = false;
+
+    for component in path.components() {
+        if needs_separator {
+            match rest.strip_prefix(separator) {
+                Some(after) => rest = after,
+                None => return false,
+            }
+        }
+
+        let (part, separated) = match component {
+            Component::Prefix(prefix) => (prefix.as_slice(), false),
+            Component::Root => (separator, false),
+            Component::Current => (P::CURRENT_DIR.as_slice(), true),
+            Component::Parent => (P::PARENT_DIR.as_slice(), true),
+            Component::Normal(name) => (name.as_slice(), true),
+            Component::Empty => (&[][..], true),
+        };
+        match rest.strip_prefix(part) {
+            Some(after) => rest = after,
+            None => return false,
+        }
+        needs_separator = separated;
+    }
+    rest.is_empty()
+}
+
+// Cuts a prefix shared with `query` back to a component boundary. `Break::find_break`
+// only sees the stored key, so when the query runs past the end of it, the whole key
+// comes back even if the query carries on inside its last component, as `/a/bc`
+// does past `/a/b`.
+fn component_prefix<'a, P: Path + ?Sized>(prefix: &'a P, query: &P) -> &'a P {
+    let (units, query) = (prefix.as_str().as_slice(), query.as_str().as_slice());
+    let at_boundary = match query.get(units.len()) {
+        Some(next) => {
+            P::is_separator(*next) || units.last().is_none_or(|last| P::is_separator(*last))
+        }
+        None => true,
+    };
+    if at_boundary {
+        return prefix;
+    }
+
+    let end = P::rfind_separator(prefix.as_str()).unwrap_or(0);
+    P::from_str(PathStr::from_slice(&units[..end]))
+}
 
 impl<K: PathOwned, V> PathTrie<K, V>
 where
     <K as Break>::Split: Borrow<K::Borrowed>,
 {
     pub fn new() -> Self {
-        Self(qp_trie::Trie::new())
+        Self::with_case_policy(CasePolicy::Sensitive)
+    }
+
+    /// Creates an empty trie whose keys match as `policy` says.
+    ///
+    /// Under [`AsciiInsensitive`](CasePolicy::AsciiInsensitive) and
+    /// [`UnicodeInsensitive`](CasePolicy::UnicodeInsensitive), keys are stored folded
+    /// to lower case, and the spelling each was first inserted with is kept beside
+    /// it. Every method that hands back a key or part of one, such as
+    /// [`find_ancestor`](Self::find_ancestor) and
+    /// [`longest_prefix`](Self::longest_prefix), gives that spelling.
+    ///
+    /// Keeping the spelling costs a second copy of every key; folding on the fly
+    /// instead would need the trie itself to compare case-insensitively, which its
+    /// byte-wise branching cannot do.
+    pub fn with_case_policy(policy: CasePolicy) -> Self {
+        Self {
+            trie: qp_trie::Trie::new(),
+            policy,
+            originals: qp_trie::Trie::new(),
+        }
     }
+
+    /// Returns how the trie compares the case of keys.
+    pub fn case_policy(&self) -> CasePolicy {
+        self.policy
+    }
+
     pub fn insert(&mut self, path: K, value: V) -> Option<V> {
-        self.0.insert(path, value)
+        let key = self.key(&path);
+        self.remember(&key, &path);
+        self.trie.insert(key, value)
+    }
+
+    pub fn get(&self, path: &K) -> Option<&V> {
+        self.trie.get(&self.key(path))
+    }
+
+    pub fn get_mut(&mut self, path: &K) -> Option<&mut V> {
+        let key = self.key(path);
+        self.trie.get_mut(&key)
+    }
+
+    pub fn remove(&mut self, path: &K) -> Option<V> {
+        let key = self.key(path);
+        self.originals.remove(&key);
+        self.trie.remove(&key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.trie.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// Returns `true` if `path` is a key in the trie.
+    ///
+    /// Only whole keys match, so a path that ends partway through a stored key's
+    /// component is never reported present.
+    pub fn contains_key(&self, path: &K) -> bool {
+        self.trie.contains_key(&self.key(path))
     }
 
     pub fn longest_prefix(&self, path: K) -> &K::Borrowed {
-        let pref = self.0.longest_common_prefix(&path).borrow();
-        pref
+        self.longest_prefix_ref(path.as_path())
+    }
+
+    /// Like [`longest_prefix`](Self::longest_prefix), but takes the query by reference.
+    ///
+    /// A query that is already in normalized form is looked up without copying it,
+    /// unless the trie folds case.
+    pub fn longest_prefix_ref(&self, path: &K::Borrowed) -> &K::Borrowed {
+        if self.policy == CasePolicy::Sensitive && is_key_form(path) {
+            let bytes: &[u8] = bytemuck::cast_slice(path.as_str().as_slice());
+            component_prefix(self.trie.longest_common_prefix(bytes).borrow(), path)
+        } else {
+            let key = self.fold(K::from_components(path.components()));
+            let prefix = component_prefix(
+                self.trie.longest_common_prefix(&key).borrow(),
+                key.as_path(),
+            );
+            self.original_prefix(prefix)
+        }
+    }
+
+    pub fn entry(&mut self, path: K) -> Entry<'_, K, V> {
+        let key = self.key(&path);
+        let original = if self.policy != CasePolicy::Sensitive && !self.originals.contains_key(&key)
+        {
+            Some((&mut self.originals, normalize_key(&path)))
+        } else {
+            None
+        };
+        Entry(self.trie.entry(key), original)
+    }
+
+    /// Inserts every entry of `other` into `self`.
+    ///
+    /// When a key exists in both, `conflict` is called with the key, the existing
+    /// value and the incoming value, and decides what to keep.
+    pub fn merge<F: FnMut(&K::Borrowed, &mut V, V)>(&mut self, mut other: Self, mut conflict: F) {
+        // other's keys are already normalized, but may not be folded the same way
+        for (path, value) in other.drain() {
+            let original = (self.policy != CasePolicy::Sensitive).then(|| path.clone());
+            let key = self.fold(path);
+            match self.trie.get_mut(&key) {
+                Some(existing) => {
+                    let path = self.originals.get(&key).unwrap_or(&key);
+                    conflict(path.as_path(), existing, value)
+                }
+                None => {
+                    if let Some(original) = original {
+                        self.originals.insert(key.clone(), original);
+                    }
+                    self.trie.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Removes every entry for which `f` returns `false`.
+    pub fn retain<F: FnMut(&K::Borrowed, &mut V) -> bool>(&mut self, mut f: F) {
+        // qp_trie can't remove while iterating, so collect the keys first.
+        let originals = &self.originals;
+        let removed = self
+            .trie
+            .iter_mut()
+            .filter_map(|(path, value)| {
+                let original = originals.get(path).unwrap_or(path);
+                (!f(original.as_path(), value)).then(|| path.clone())
+            })
+            .collect::<Vec<_>>();
+
+        for path in removed {
+            self.trie.remove(&path);
+            self.originals.remove(&path);
+        }
+    }
+
+    /// Removes every entry at or below `prefix`, returning how many were removed.
+    pub fn remove_prefix(&mut self, prefix: &K) -> usize {
+        let removed = self
+            .subtree(&self.key(prefix))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+
+        for path in &removed {
+            self.trie.remove(path);
+            self.originals.remove(path);
+        }
+        removed.len()
+    }
+
+    /// Counts the entries at or below `prefix`, the same ones [`remove_prefix`](Self::remove_prefix)
+    /// would remove.
+    pub fn count_under_prefix(&self, prefix: &K) -> usize {
+        self.subtree(&self.key(prefix)).count()
+    }
+
+    /// Iterates the entries exactly one component below `prefix`, like a directory
+    /// listing that does not recurse.
+    ///
+    /// Listing `/a` yields `/a/b` and `/a/c`, but neither `/a` itself nor `/a/b/d`.
+    pub fn iter_children(&self, prefix: &K) -> impl Iterator<Item = (&K::Borrowed, &V)> {
+        let prefix = self.key(prefix);
+        let depth = prefix.as_path().components().count() + 1;
+        let children = self
+            .subtree(&prefix)
+            .filter(|(path, _)| path.as_path().components().count() == depth)
+            .map(|(path, value)| (self.original(path).as_path(), value))
+            .collect::<Vec<_>>();
+        children.into_iter()
+    }
+
+    /// Iterates the entries whose keys are prefixes of `path`, compared component-wise,
+    /// from shortest to longest. `path` itself is included if it is a key.
+    pub fn ancestors_in_trie(&self, path: &K) -> impl Iterator<Item = (&K::Borrowed, &V)> {
+        let path = self.key(path);
+        let mut lengths = Vec::new();
+        let mut ancestor = Some(path.as_path());
+        while let Some(current) = ancestor {
+            lengths.push(current.as_str().len());
+            ancestor = current.parent();
+        }
+
+        let query = path.as_path().as_str().as_slice();
+        let found = lengths
+            .into_iter()
+            .rev()
+            .filter_map(|len| self.stored_entry(&query[..len]))
+            .collect::<Vec<_>>();
+        found.into_iter()
+    }
+
+    /// Returns the longest key that is a prefix of `path`, compared component-wise, or
+    /// `None` if there is no such key.
+    ///
+    /// Unlike [`longest_prefix`](Self::longest_prefix), which returns the empty path
+    /// when nothing matches, this only ever returns keys that are actually stored, so
+    /// a stored empty key is told apart from no match at all.
+    pub fn longest_prefix_opt(&self, path: &K::Borrowed) -> Option<&K::Borrowed> {
+        self.nearest_ancestor(path, |_| true).map(|(key, _)| key)
+    }
+
+    /// Returns the longest key that is a prefix of `path`, compared component-wise,
+    /// whose value satisfies `pred`.
+    ///
+    /// Ancestors are tried from longest to shortest, so this finds, say, the nearest
+    /// enclosing mount that is writable.
+    pub fn find_ancestor<F: Fn(&V) -> bool>(
+        &self,
+        path: &K,
+        pred: F,
+    ) -> Option<(&K::Borrowed, &V)> {
+        self.nearest_ancestor(path.as_path(), pred)
+    }
+
+    /// Reports how `path` overlaps the keys already in the trie, or `None` if it is
+    /// neither equal to, above, nor below any of them.
+    ///
+    /// If `path` is both below one key and above another, it is reported as a
+    /// [`Descendant`](Conflict::Descendant).
+    pub fn would_conflict(&self, path: &K) -> Option<Conflict> {
+        let path = self.key(path);
+        if self.trie.contains_key(&path) {
+            Some(Conflict::Equal)
+        } else if self.nearest_ancestor(path.as_path(), |_| true).is_some() {
+            Some(Conflict::Descendant)
+        } else if self.subtree(&path).next().is_some() {
+            Some(Conflict::Ancestor)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the mount for `path`: the longest key that is a prefix of it, along with
+    /// its value and the rest of `path` below that key.
+    ///
+    /// Returns `None` if no key is a prefix of `path`.
+    pub fn resolve_mount(&self, path: &K) -> Option<(&K::Borrowed, &V, K)> {
+        let path = normalize_key(path);
+        let (mount, value) = self.nearest_ancestor(path.as_path(), |_| true)?;
+        // the mount may be spelled differently, but has one component per component
+        // it matched
+        let rest = path.as_path().tail_from(depth(mount));
+
+        let mut remainder = K::new();
+        remainder.push(rest.as_str());
+        Some((mount, value, remainder))
+    }
+
+    fn nearest_ancestor<F: Fn(&V) -> bool>(
+        &self,
+        path: &K::Borrowed,
+        pred: F,
+    ) -> Option<(&K::Borrowed, &V)> {
+        let owned;
+        let mut ancestor = if self.policy == CasePolicy::Sensitive && is_key_form(path) {
+            Some(path)
+        } else {
+            owned = self.fold(K::from_components(path.components()));
+            Some(owned.as_path())
+        };
+
+        while let Some(current) = ancestor {
+            match self.stored_entry(current.as_str().as_slice()) {
+                Some((key, value)) if pred(value) => return Some((key, value)),
+                _ => ancestor = current.parent(),
+            }
+        }
+        None
+    }
+
+    // Looks up the entry whose key is exactly `key`, handing back the stored key so
+    // it can outlive the query.
+    fn stored_entry(
+        &self,
+        key: &[<<K::Borrowed as Path>::Str as PathStr>::ComponentType],
+    ) -> Option<(&K::Borrowed, &V)> {
+        let key: &[u8] = bytemuck::cast_slice(key);
+        if !self.trie.contains_key(key) {
+            return None;
+        }
+        self.trie
+            .iter_prefix(key)
+            .find(|(stored, _)| Borrow::<[u8]>::borrow(*stored).len() == key.len())
+            .map(|(stored, value)| (self.original(stored).as_path(), value))
+    }
+
+    // Returns the leading components of a stored key's original spelling that fold to
+    // `prefix`. Folding can change the length of a component but never the number of
+    // them, so the cut is made by counting components.
+    fn original_prefix<'a>(&'a self, prefix: &'a K::Borrowed) -> &'a K::Borrowed {
+        let last = match prefix.components().count().checked_sub(1) {
+            Some(last) if self.policy != CasePolicy::Sensitive => last,
+            _ => return prefix,
+        };
+        let bytes: &[u8] = bytemuck::cast_slice(prefix.as_str().as_slice());
+        let original = match self
+            .trie
+            .iter_prefix(bytes)
+            .find(|(key, _)| key.as_path().starts_with(prefix))
+        {
+            Some((key, _)) => self.original(key).as_path(),
+            None => return prefix,
+        };
+
+        let end = original.component_range(last).map_or(0, |range| range.end);
+        K::Borrowed::from_str(PathStr::from_slice(&original.as_str().as_slice()[..end]))
+    }
+
+    // Returns the spelling `key` was first inserted with.
+    fn original<'a>(&'a self, key: &'a K) -> &'a K {
+        self.originals.get(key).unwrap_or(key)
+    }
+
+    // Records the spelling of `path` under its folded `key`, unless one is already
+    // recorded or nothing is folded.
+    fn remember(&mut self, key: &K, path: &K) {
+        if self.policy != CasePolicy::Sensitive && !self.originals.contains_key(key) {
+            self.originals.insert(key.clone(), normalize_key(path));
+        }
+    }
+
+    // Normalizes `path` and folds it as the policy says.
+    fn key(&self, path: &K) -> K {
+        self.fold(normalize_key(path))
+    }
+
+    fn fold(&self, mut key: K) -> K {
+        match self.policy {
+            CasePolicy::Sensitive => {}
+            CasePolicy::AsciiInsensitive => key.make_ascii_lowercase(),
+            CasePolicy::UnicodeInsensitive => key.make_lowercase(),
+        }
+        key
+    }
+
+    // Iterates the entries at or below the normalized `prefix`. A byte-wise prefix
+    // search alone would also find `/ab` under `/a`, so matches are checked
+    // component-wise.
+    fn subtree<'a: 'b, 'b>(&'a self, prefix: &'b K) -> impl Iterator<Item = (&'a K, &'a V)> + 'b {
+        self.trie
+            .iter_prefix(prefix)
+            .filter(|(path, _)| path.as_path().starts_with(prefix.as_path()))
+    }
+
+    /// Iterates mutably over every value in the trie.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.trie.values_mut()
+    }
+
+    /// Iterates the keys whose values satisfy `pred`, in no particular order.
+    ///
+    /// This scans every entry.
+    pub fn keys_with_value<F: Fn(&V) -> bool>(
+        &self,
+        pred: F,
+    ) -> impl Iterator<Item = &K::Borrowed> {
+        self.trie
+            .iter()
+            .filter(move |(_, value)| pred(value))
+            .map(|(path, _)| self.original(path).as_path())
+    }
+
+    /// Returns the value at `path`, inserting the result of `f` first if there is none.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, path: K, f: F) -> &mut V {
+        self.entry(path).or_insert_with(f)
+    }
+
+    /// Returns the value at `path`, inserting `V::default()` first if there is none.
+    pub fn get_mut_or_default(&mut self, path: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.entry(path).or_default()
+    }
+
+    /// Removes every entry from the trie, yielding them as owned pairs.
+    ///
+    /// Keys are yielded in their normalized form, spelled as they were first
+    /// inserted. The trie is empty as soon as this returns, even if the iterator is
+    /// not used up.
+    pub fn drain(&mut self) -> impl Iterator<Item = (K, V)> {
+        let mut originals = core::mem::replace(&mut self.originals, qp_trie::Trie::new());
+        core::mem::replace(&mut self.trie, qp_trie::Trie::new())
+            .into_iter()
+            .map(move |(key, value)| (originals.remove(&key).unwrap_or(key), value))
+    }
+
+    /// Copies every entry into a vector, sorted by key.
+    ///
+    /// Keys are in normalized form and compared code unit by code unit, so the order
+    /// does not depend on the order entries were inserted in. See
+    /// [`from_sorted_vec`](Self::from_sorted_vec) for the way back.
+    pub fn to_sorted_vec(&self) -> Vec<(K, V)>
+    where
+        V: Clone,
+    {
+        let mut entries = self
+            .trie
+            .iter()
+            .map(|(path, value)| (self.original(path).clone(), value.clone()))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| {
+            let (a, b) = (a.as_path().as_str(), b.as_path().as_str());
+            a.as_slice().cmp(b.as_slice())
+        });
+        entries
+    }
+
+    /// Builds a trie from the entries given by [`to_sorted_vec`](Self::to_sorted_vec).
+    ///
+    /// `qp_trie` has no bulk construction, so this is no faster than collecting the
+    /// entries, and works just as well if they are not sorted. The trie is case
+    /// sensitive; see [`from_sorted_vec_with_policy`](Self::from_sorted_vec_with_policy)
+    /// to rebuild one that folds case.
+    pub fn from_sorted_vec(entries: Vec<(K, V)>) -> Self {
+        Self::from_sorted_vec_with_policy(entries, CasePolicy::Sensitive)
+    }
+
+    /// Builds a trie with the given case policy from the entries given by
+    /// [`to_sorted_vec`](Self::to_sorted_vec).
+    pub fn from_sorted_vec_with_policy(entries: Vec<(K, V)>, policy: CasePolicy) -> Self {
+        let mut trie = Self::with_case_policy(policy);
+        trie.extend(entries);
+        trie
+    }
+
+    pub fn clear(&mut self) {
+        // Trie::clear does not reset the entry count.
+        self.trie = qp_trie::Trie::new();
+        self.originals = qp_trie::Trie::new();
+    }
+}
+
+// Counts the components of `path` below any prefix and root.
+fn depth<P: Path + ?Sized>(path: &P) -> usize {
+    path.components().skip_prefix().count()
+}
+
+/// How a [`PathTrie`] compares the case of keys, as given to
+/// [`PathTrie::with_case_policy`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CasePolicy {
+    /// Keys match only if their case is the same.
+    #[default]
+    Sensitive,
+    /// ASCII letters match regardless of case, as on FAT.
+    AsciiInsensitive,
+    /// Characters match if their Unicode lower case forms are the same.
+    UnicodeInsensitive,
+}
+
+/// How a path overlaps the keys of a [`PathTrie`], as found by
+/// [`PathTrie::would_conflict`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Conflict {
+    /// The path is already a key.
+    Equal,
+    /// The path is a prefix of an existing key, such as `/a` when `/a/b` is a key.
+    Ancestor,
+    /// An existing key is a prefix of the path, such as `/a/b` when `/a` is a key.
+    Descendant,
+}
+
+/// A view into a single entry of a [`PathTrie`], which may be vacant or occupied.
+///
+/// If the trie folds case, the spelling of the key is recorded only when a value is
+/// inserted.
+#[derive(Debug)]
+pub struct Entry<'a, K: PathOwned, V>(
+    qp_trie::Entry<'a, K, V>,
+    Option<(&'a mut qp_trie::Trie<K, K>, K)>,
+);
+
+impl<'a, K: PathOwned, V> Entry<'a, K, V> {
+    /// Returns the key as it is stored, normalized and folded.
+    pub fn key(&self) -> &K {
+        self.0.key()
+    }
+
+    /// Inserts `default` if the entry is vacant, and returns the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        let Entry(entry, original) = self;
+        if let (qp_trie::Entry::Vacant(_), Some((originals, original))) = (&entry, original) {
+            originals.insert(entry.key().clone(), original);
+        }
+        entry.or_insert_with(default)
+    }
+
+    /// Inserts `V::default()` if the entry is vacant, and returns the value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Calls `f` on the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self.0 {
+            qp_trie::Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Self(qp_trie::Entry::Occupied(entry), self.1)
+            }
+            vacant => Self(vacant, self.1),
+        }
+    }
+}
+
+impl<K: PathOwned> PathTrie<K, K>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    /// Follows redirects from `path`, treating each key as a redirect to its value.
+    ///
+    /// The longest key that is a prefix of the path is replaced with its target, and
+    /// the result is looked up again until no key matches. Returns an error if that
+    /// has not happened after `limit` redirects, which is how cycles are caught.
+    pub fn resolve(&self, path: &K, limit: usize) -> Result<K, RedirectLimitError> {
+        let mut current = normalize_key(path);
+        for _ in 0..=limit {
+            let (from, to) = match self.nearest_ancestor(current.as_path(), |_| true) {
+                Some(redirect) => redirect,
+                None => return Ok(current),
+            };
+
+            let rest = current.as_path().tail_from(depth(from));
+            let mut next = to.clone();
+            if !rest.is_empty_path() {
+                next.push(rest.as_str());
+            }
+            current = normalize_key(&next);
+        }
+        Err(RedirectLimitError { limit })
+    }
+}
+
+/// The error returned by [`PathTrie::resolve`] when redirects are still being
+/// followed after the limit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RedirectLimitError {
+    limit: usize,
+}
+
+impl RedirectLimitError {
+    /// Returns the number of redirects that were followed.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for RedirectLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path not resolved after {} redirects", self.limit)
+    }
+}
+
+impl Error for RedirectLimitError {}
+
+impl<K: PathOwned, V> Default for PathTrie<K, V>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PathOwned, V> FromIterator<(K, V)> for PathTrie<K, V>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<K: PathOwned, V> Extend<(K, V)> for PathTrie<K, V>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (path, value) in iter {
+            self.insert(path, value);
+        }
+    }
+}
+
+/// A stack of [`PathTrie`]s looked up from the top down, like a union mount.
+///
+/// Each lookup is answered by the topmost layer that has an answer, so a later layer
+/// shadows the ones below it without either being changed.
+#[derive(Clone, Debug)]
+pub struct OverlayTrie<K: PathOwned, V> {
+    layers: Vec<PathTrie<K, V>>,
+}
+
+impl<K: PathOwned, V> OverlayTrie<K, V>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Puts `layer` on top of the stack, above every layer already in it.
+    pub fn push_layer(&mut self, layer: PathTrie<K, V>) {
+        self.layers.push(layer)
+    }
+
+    /// Removes the top layer and returns it.
+    pub fn pop_layer(&mut self) -> Option<PathTrie<K, V>> {
+        self.layers.pop()
+    }
+
+    /// Returns the layers from the bottom up.
+    pub fn layers(&self) -> &[PathTrie<K, V>] {
+        &self.layers
+    }
+
+    /// Returns the value at `path` in the topmost layer that has one.
+    pub fn get(&self, path: &K) -> Option<&V> {
+        self.layers.iter().rev().find_map(|layer| layer.get(path))
+    }
+
+    /// Returns the longest key that is a prefix of `path` in the topmost layer that
+    /// has one, along with its value.
+    ///
+    /// A layer with any matching key hides longer matches in the layers below it, as
+    /// a mount over `/` hides everything mounted beneath it.
+    pub fn longest_prefix(&self, path: &K) -> Option<(&K::Borrowed, &V)> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.find_ancestor(path, |_| true))
+    }
+}
+
+impl<K: PathOwned, V> Default for OverlayTrie<K, V>
+where
+    <K as Break>::Split: Borrow<K::Borrowed>,
+{
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::path::{Path, U16Path, U16PathBuf, U8Path, U8PathBuf};
-    use crate::trie::PathTrie;
+    use crate::trie::{CasePolicy, Conflict, OverlayTrie, PathTrie, RedirectLimitError};
     use bstr::BStr;
     use widestring::u16str;
 
@@ -50,4 +774,688 @@ mod test {
         let pref = trie.longest_prefix(U8PathBuf::from("/hello/world/spad"));
         assert_eq!(U8Path::from_str(BStr::new(b"/hello/world")), pref)
     }
+
+    #[test]
+    pub fn case_insensitive_u8() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::AsciiInsensitive);
+        trie.insert(U8PathBuf::from("/Hello/World"), 1);
+
+        let pref = trie.longest_prefix(U8PathBuf::from("/hello/WORLD/file"));
+        assert_eq!(U8Path::from_str(BStr::new(b"/Hello/World")), pref)
+    }
+
+    #[test]
+    pub fn case_insensitive_u16() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::AsciiInsensitive);
+        trie.insert(U16PathBuf::from("/Hello/World"), 1);
+
+        let pref = trie.longest_prefix(U16PathBuf::from("/hello/WORLD/file"));
+        assert_eq!(U16Path::from_str(u16str!("/Hello/World")), pref)
+    }
+
+    #[test]
+    pub fn default() {
+        let trie: PathTrie<U8PathBuf, ()> = Default::default();
+        assert_eq!(trie.longest_prefix(U8PathBuf::from("/a")), U8Path::empty());
+
+        let trie: PathTrie<U16PathBuf, ()> = Default::default();
+        assert_eq!(trie.case_policy(), CasePolicy::Sensitive);
+        assert_eq!(
+            trie.longest_prefix(U16PathBuf::from("/a")),
+            U16Path::empty()
+        );
+    }
+
+    #[test]
+    pub fn len() {
+        let mut trie = PathTrie::new();
+        assert!(trie.is_empty());
+
+        trie.insert(U8PathBuf::from("/hello/world"), 1);
+        trie.insert(U8PathBuf::from("/hello/world/spam"), 2);
+        trie.insert(U8PathBuf::from("/hello/spam/eggs"), 1);
+        assert_eq!(trie.len(), 3);
+
+        assert_eq!(trie.remove(&U8PathBuf::from("/hello/world")), Some(1));
+        assert_eq!(trie.len(), 2);
+        assert!(!trie.is_empty());
+    }
+
+    #[test]
+    pub fn contains_key() {
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/hello/world"), 1);
+
+        assert!(trie.contains_key(&U16PathBuf::from("/hello/world")));
+        assert!(!trie.contains_key(&U16PathBuf::from("/hello/wor")));
+        assert!(!trie.contains_key(&U16PathBuf::from("/hello")));
+    }
+
+    #[test]
+    pub fn entry() {
+        let mut trie = PathTrie::new();
+        for _ in 0..2 {
+            trie.entry(U8PathBuf::from("/mnt/disk"))
+                .and_modify(|count: &mut u32| *count += 1)
+                .or_insert(1);
+        }
+
+        assert_eq!(trie.len(), 1);
+        assert_eq!(*trie.entry(U8PathBuf::from("/mnt/disk")).or_insert(0), 2);
+    }
+
+    #[test]
+    pub fn retain() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/hello/world"), 1);
+        trie.insert(U8PathBuf::from("/hello/world/spam"), 2);
+        trie.insert(U8PathBuf::from("/hello/spam/eggs"), 3);
+
+        trie.retain(|_, value| *value > 1);
+        assert_eq!(trie.len(), 2);
+        assert!(!trie.contains_key(&U8PathBuf::from("/hello/world")));
+        assert!(trie.contains_key(&U8PathBuf::from("/hello/world/spam")));
+        assert!(trie.contains_key(&U8PathBuf::from("/hello/spam/eggs")));
+    }
+
+    #[test]
+    pub fn clear() {
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/hello/world"), 1);
+        trie.insert(U16PathBuf::from("/hello/world/spam"), 2);
+
+        trie.clear();
+        assert_eq!(trie.len(), 0);
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    pub fn remove_prefix() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a/b"), 1);
+        trie.insert(U8PathBuf::from("/a/c"), 2);
+        trie.insert(U8PathBuf::from("/ab"), 3);
+
+        assert_eq!(trie.remove_prefix(&U8PathBuf::from("/a")), 2);
+        assert_eq!(trie.len(), 1);
+        assert!(trie.contains_key(&U8PathBuf::from("/ab")));
+    }
+
+    #[test]
+    pub fn count_under_prefix() {
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/hello/world"), 1);
+        trie.insert(U16PathBuf::from("/hello/world/spam"), 2);
+        trie.insert(U16PathBuf::from("/hellothere"), 3);
+
+        assert_eq!(trie.count_under_prefix(&U16PathBuf::from("/hello")), 2);
+        assert_eq!(
+            trie.count_under_prefix(&U16PathBuf::from("/hello/world/spam")),
+            1
+        );
+        assert_eq!(trie.count_under_prefix(&U16PathBuf::from("/eggs")), 0);
+    }
+
+    #[test]
+    pub fn normalized_keys() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a//b"), 1);
+        assert_eq!(trie.get(&U8PathBuf::from("/a/b")), Some(&1));
+        assert_eq!(trie.get(&U8PathBuf::from("/a/./b/")), Some(&1));
+
+        trie.insert(U8PathBuf::from("/a\\b"), 2);
+        assert_eq!(trie.len(), 1);
+        assert_eq!(trie.get(&U8PathBuf::from("/a/b")), Some(&2));
+
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/a//b"), 1);
+        assert_eq!(trie.get(&U16PathBuf::from("/a/b")), Some(&1));
+        assert!(trie.contains_key(&U16PathBuf::from("\\a\\b\\")));
+    }
+
+    #[test]
+    pub fn from_iter() {
+        let mut trie = [
+            (U8PathBuf::from("/hello/world"), 1),
+            (U8PathBuf::from("/hello/world/spam"), 2),
+            (U8PathBuf::from("/hello/spam/eggs"), 1),
+        ]
+        .into_iter()
+        .collect::<PathTrie<_, _>>();
+        assert_eq!(trie.len(), 3);
+
+        let pref = trie.longest_prefix(U8PathBuf::from("/hello/world/spad"));
+        assert_eq!(U8Path::from_str(BStr::new(b"/hello/world")), pref);
+
+        trie.extend([(U8PathBuf::from("/eggs"), 3)]);
+        assert_eq!(trie.len(), 4);
+    }
+
+    #[test]
+    pub fn merge() {
+        let mut lower = PathTrie::new();
+        lower.insert(U8PathBuf::from("/a"), 1);
+        lower.insert(U8PathBuf::from("/b"), 2);
+
+        let mut upper = PathTrie::new();
+        upper.insert(U8PathBuf::from("/b"), 3);
+        upper.insert(U8PathBuf::from("/c"), 4);
+
+        lower.merge(upper, |_, existing, incoming| *existing = incoming);
+        assert_eq!(lower.len(), 3);
+        assert_eq!(lower.get(&U8PathBuf::from("/a")), Some(&1));
+        assert_eq!(lower.get(&U8PathBuf::from("/b")), Some(&3));
+        assert_eq!(lower.get(&U8PathBuf::from("/c")), Some(&4));
+    }
+
+    #[test]
+    pub fn ancestors_in_trie() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a"), 1);
+        trie.insert(U8PathBuf::from("/a/b"), 2);
+        trie.insert(U8PathBuf::from("/a/b/c"), 3);
+        trie.insert(U8PathBuf::from("/a/bc"), 4);
+        trie.insert(U8PathBuf::from("/x"), 5);
+
+        let ancestors = trie
+            .ancestors_in_trie(&U8PathBuf::from("/a//b/c/d"))
+            .map(|(path, value)| (path.as_str(), *value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ancestors,
+            [
+                (BStr::new("/a"), 1),
+                (BStr::new("/a/b"), 2),
+                (BStr::new("/a/b/c"), 3)
+            ]
+        );
+
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/"), 1);
+        trie.insert(U16PathBuf::from("/a"), 2);
+        let ancestors = trie
+            .ancestors_in_trie(&U16PathBuf::from("/a"))
+            .map(|(_, value)| *value)
+            .collect::<Vec<_>>();
+        assert_eq!(ancestors, [1, 2]);
+    }
+
+    #[test]
+    pub fn values_mut() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a"), 1);
+        trie.insert(U8PathBuf::from("/a/b"), 2);
+        trie.insert(U8PathBuf::from("/c"), 3);
+
+        for value in trie.values_mut() {
+            *value += 10;
+        }
+        assert_eq!(trie.get(&U8PathBuf::from("/a")), Some(&11));
+        assert_eq!(trie.get(&U8PathBuf::from("/a/b")), Some(&12));
+        assert_eq!(trie.get(&U8PathBuf::from("/c")), Some(&13));
+    }
+
+    #[test]
+    pub fn get_or_insert_with() {
+        let mut trie = PathTrie::new();
+        *trie.get_or_insert_with(U16PathBuf::from("/a"), Vec::new) = vec![1];
+        trie.get_or_insert_with(U16PathBuf::from("/a//"), || unreachable!())
+            .push(2);
+        assert_eq!(trie.get(&U16PathBuf::from("/a")), Some(&vec![1, 2]));
+        assert_eq!(trie.len(), 1);
+    }
+
+    #[test]
+    pub fn iter_children() {
+        let mut trie = PathTrie::new();
+        for (i, path) in ["/a", "/a/b", "/a/c/", "/a/b/d", "/ab", "/e"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(U8PathBuf::from(*path), i);
+        }
+
+        let mut children = trie
+            .iter_children(&U8PathBuf::from("/a//"))
+            .map(|(path, value)| (path.as_str(), *value))
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(children, [("/a/b".into(), 1), ("/a/c".into(), 2)]);
+
+        let mut children = trie
+            .iter_children(&U8PathBuf::from("/"))
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(children, ["/a", "/ab", "/e"]);
+
+        assert_eq!(trie.iter_children(&U8PathBuf::from("/a/b/d")).count(), 0);
+        assert_eq!(trie.iter_children(&U8PathBuf::from("/x")).count(), 0);
+    }
+
+    #[test]
+    pub fn keys_with_value() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/mnt/a"), "ro");
+        trie.insert(U8PathBuf::from("/mnt/b/"), "rw");
+        trie.insert(U8PathBuf::from("/mnt/c"), "ro");
+
+        let mut keys = trie
+            .keys_with_value(|value| *value == "ro")
+            .map(U8Path::as_str)
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["/mnt/a", "/mnt/c"]);
+
+        let keys = trie
+            .keys_with_value(|value| *value == "rw")
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [U8Path::from_str(BStr::new("/mnt/b"))]);
+        assert_eq!(trie.keys_with_value(|_| false).count(), 0);
+    }
+
+    #[test]
+    pub fn get_mut_or_default() {
+        let mut trie = PathTrie::new();
+        for path in ["/a/b", "/a//b/", "/a/./b"] {
+            *trie.get_mut_or_default(U8PathBuf::from(path)) += 1usize;
+        }
+        *trie.entry(U8PathBuf::from("/c")).or_default() += 1;
+        assert_eq!(trie.get(&U8PathBuf::from("/a/b")), Some(&3));
+        assert_eq!(trie.get(&U8PathBuf::from("/c")), Some(&1));
+        assert_eq!(trie.len(), 2);
+    }
+
+    #[test]
+    pub fn longest_prefix_ref() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/hello/world"), 1);
+        trie.insert(U8PathBuf::from("/hello/spam/eggs"), 2);
+
+        let query = U8Path::from_str(BStr::new("/hello/world/file"));
+        assert_eq!(trie.longest_prefix_ref(query).as_str(), "/hello/world");
+        let query = U8Path::from_str(BStr::new(r"/hello\world//./file"));
+        assert_eq!(trie.longest_prefix_ref(query).as_str(), "/hello/world");
+
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from(r"C:\a"), 1);
+        let query = U16Path::from_str(u16str!("C:/a/b"));
+        assert_eq!(trie.longest_prefix_ref(query).as_str(), u16str!("C:/a"));
+    }
+
+    #[test]
+    pub fn drain() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a"), 1);
+        trie.insert(U8PathBuf::from("/a//b"), 2);
+        trie.insert(U8PathBuf::from("/c"), 3);
+
+        let mut drained = trie.drain().collect::<Vec<_>>();
+        assert!(trie.is_empty());
+        assert_eq!(trie.len(), 0);
+
+        drained.sort_by_key(|(_, value)| *value);
+        assert_eq!(
+            drained,
+            vec![
+                (U8PathBuf::from("/a"), 1),
+                (U8PathBuf::from("/a/b"), 2),
+                (U8PathBuf::from("/c"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn clone() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a"), 1);
+        trie.insert(U8PathBuf::from("/a/b"), 2);
+
+        let mut snapshot = trie.clone();
+        snapshot.insert(U8PathBuf::from("/c"), 3);
+        *snapshot.get_mut(&U8PathBuf::from("/a")).unwrap() = 10;
+        snapshot.remove(&U8PathBuf::from("/a/b"));
+
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get(&U8PathBuf::from("/a")), Some(&1));
+        assert_eq!(trie.get(&U8PathBuf::from("/a/b")), Some(&2));
+        assert_eq!(trie.get(&U8PathBuf::from("/c")), None);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.get(&U8PathBuf::from("/a")), Some(&10));
+    }
+
+    #[test]
+    pub fn longest_prefix_opt() {
+        let trie = PathTrie::<U8PathBuf, ()>::new();
+        let query = U8Path::from_str(BStr::new("/a/b"));
+        assert_eq!(trie.longest_prefix_opt(query), None);
+
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a"), 1);
+        trie.insert(U8PathBuf::from("/a/b/c"), 2);
+        assert_eq!(
+            trie.longest_prefix_opt(U8Path::from_str(BStr::new("/a//b/d"))),
+            Some(U8Path::from_str(BStr::new("/a")))
+        );
+        assert_eq!(
+            trie.longest_prefix_opt(U8Path::from_str(BStr::new("/a/b/c/d"))),
+            Some(U8Path::from_str(BStr::new("/a/b/c")))
+        );
+        assert_eq!(
+            trie.longest_prefix_opt(U8Path::from_str(BStr::new("/ab"))),
+            None
+        );
+        assert_eq!(
+            trie.longest_prefix_opt(U8Path::from_str(BStr::new("/x"))),
+            None
+        );
+
+        trie.insert(U8PathBuf::from("/"), 0);
+        assert_eq!(
+            trie.longest_prefix_opt(U8Path::from_str(BStr::new("/x"))),
+            Some(U8Path::root())
+        );
+    }
+
+    #[test]
+    pub fn surrogate_pairs() {
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/a/😀/x"), 1);
+        trie.insert(U16PathBuf::from("/a/😀b"), 2);
+
+        let pref = trie.longest_prefix(U16PathBuf::from("/a/😁/x"));
+        assert_eq!(pref.as_str(), u16str!("/a"));
+        let pref = trie.longest_prefix(U16PathBuf::from("/a/😀/x/y"));
+        assert_eq!(pref.as_str().to_string().unwrap(), "/a/😀/x");
+    }
+
+    #[test]
+    pub fn find_ancestor() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/"), true);
+        trie.insert(U8PathBuf::from("/mnt"), false);
+        trie.insert(U8PathBuf::from("/mnt/rw"), true);
+        trie.insert(U8PathBuf::from("/mnt/rw/ro"), false);
+
+        let query = U8PathBuf::from("/mnt/rw/ro/file");
+        assert_eq!(
+            trie.find_ancestor(&query, |writable| *writable),
+            Some((U8Path::from_str(BStr::new("/mnt/rw")), &true))
+        );
+        assert_eq!(
+            trie.find_ancestor(&query, |writable| !*writable),
+            Some((U8Path::from_str(BStr::new("/mnt/rw/ro")), &false))
+        );
+
+        let query = U8PathBuf::from("/mnt//other");
+        assert_eq!(
+            trie.find_ancestor(&query, |writable| *writable),
+            Some((U8Path::root(), &true))
+        );
+        assert_eq!(trie.find_ancestor(&query, |_| false), None);
+    }
+
+    #[test]
+    pub fn resolve() {
+        let mut redirects = PathTrie::new();
+        redirects.insert(U8PathBuf::from("/a"), U8PathBuf::from("/b"));
+        let resolved = redirects.resolve(&U8PathBuf::from("/a//x/y"), 8).unwrap();
+        assert_eq!(resolved.as_str(), "/b/x/y");
+        let resolved = redirects.resolve(&U8PathBuf::from("/ab/x"), 8).unwrap();
+        assert_eq!(resolved.as_str(), "/ab/x");
+        let resolved = redirects.resolve(&U8PathBuf::from("/a"), 8).unwrap();
+        assert_eq!(resolved.as_str(), "/b");
+
+        redirects.insert(U8PathBuf::from("/b/x"), U8PathBuf::from("/c"));
+        let resolved = redirects.resolve(&U8PathBuf::from("/a/x/y"), 8).unwrap();
+        assert_eq!(resolved.as_str(), "/c/y");
+        assert!(redirects.resolve(&U8PathBuf::from("/a/x/y"), 1).is_err());
+
+        redirects.insert(U8PathBuf::from("/c"), U8PathBuf::from("/a/x"));
+        assert_eq!(
+            redirects.resolve(&U8PathBuf::from("/a/x"), 8),
+            Err(RedirectLimitError { limit: 8 })
+        );
+    }
+
+    #[test]
+    pub fn sorted_vec() {
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/b"), 2);
+        trie.insert(U16PathBuf::from(r"\a\\c"), 3);
+        trie.insert(U16PathBuf::from("/a"), 1);
+        trie.insert(U16PathBuf::from("/\u{100}"), 4);
+
+        let entries = trie.to_sorted_vec();
+        assert_eq!(
+            entries.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+            [1, 3, 2, 4]
+        );
+
+        let rebuilt = PathTrie::from_sorted_vec(entries);
+        assert_eq!(rebuilt.len(), trie.len());
+        for query in ["/a/c/d", "/a/x", "/b/c", "/", "/\u{100}/x"] {
+            let query = U16PathBuf::from(query);
+            assert_eq!(
+                rebuilt.longest_prefix(query.clone()),
+                trie.longest_prefix(query.clone())
+            );
+            assert_eq!(rebuilt.get(&query), trie.get(&query));
+        }
+    }
+
+    #[test]
+    pub fn resolve_mount() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/"), "root");
+        trie.insert(U8PathBuf::from("/mnt/disk"), "disk");
+
+        let (mount, value, rest) = trie
+            .resolve_mount(&U8PathBuf::from("/mnt/disk/file.txt"))
+            .unwrap();
+        assert_eq!(mount.as_str(), "/mnt/disk");
+        assert_eq!(*value, "disk");
+        assert_eq!(rest.as_str(), "file.txt");
+
+        let (mount, _, rest) = trie.resolve_mount(&U8PathBuf::from("/mnt//disk")).unwrap();
+        assert_eq!(mount.as_str(), "/mnt/disk");
+        assert!(rest.is_empty());
+
+        let (mount, value, rest) = trie
+            .resolve_mount(&U8PathBuf::from("/mnt/diskette/a"))
+            .unwrap();
+        assert_eq!(mount, U8Path::root());
+        assert_eq!(*value, "root");
+        assert_eq!(rest.as_str(), "mnt/diskette/a");
+
+        trie.remove(&U8PathBuf::from("/"));
+        assert!(trie.resolve_mount(&U8PathBuf::from("/mnt/a")).is_none());
+    }
+
+    #[test]
+    pub fn overlay() {
+        let mut bottom = PathTrie::new();
+        bottom.insert(U8PathBuf::from("/data"), "bottom");
+        bottom.insert(U8PathBuf::from("/data/saves"), "bottom saves");
+        bottom.insert(U8PathBuf::from("/bin"), "bottom bin");
+        let mut top = PathTrie::new();
+        top.insert(U8PathBuf::from("/data/"), "top");
+
+        let mut overlay = OverlayTrie::new();
+        overlay.push_layer(bottom);
+        assert_eq!(overlay.get(&U8PathBuf::from("/data")), Some(&"bottom"));
+        overlay.push_layer(top);
+
+        assert_eq!(overlay.get(&U8PathBuf::from("/data")), Some(&"top"));
+        assert_eq!(overlay.get(&U8PathBuf::from("/bin")), Some(&"bottom bin"));
+        assert_eq!(overlay.get(&U8PathBuf::from("/nope")), None);
+
+        let (key, value) = overlay
+            .longest_prefix(&U8PathBuf::from("/data/saves/1"))
+            .unwrap();
+        assert_eq!((key.as_str(), *value), ("/data".into(), "top"));
+        let (key, value) = overlay.longest_prefix(&U8PathBuf::from("/bin/sh")).unwrap();
+        assert_eq!((key.as_str(), *value), ("/bin".into(), "bottom bin"));
+        assert!(overlay.longest_prefix(&U8PathBuf::from("/etc")).is_none());
+
+        // the layers themselves are untouched
+        assert_eq!(overlay.layers().len(), 2);
+        let top = overlay.pop_layer().unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(overlay.get(&U8PathBuf::from("/data")), Some(&"bottom"));
+    }
+
+    #[test]
+    pub fn would_conflict() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a/b"), ());
+        trie.insert(U8PathBuf::from("/c"), ());
+
+        let conflict = |path| trie.would_conflict(&U8PathBuf::from(path));
+        assert_eq!(conflict("/a/b"), Some(Conflict::Equal));
+        assert_eq!(conflict("/a//b/"), Some(Conflict::Equal));
+        assert_eq!(conflict("/a"), Some(Conflict::Ancestor));
+        assert_eq!(conflict("/"), Some(Conflict::Ancestor));
+        assert_eq!(conflict("/a/b/c"), Some(Conflict::Descendant));
+        assert_eq!(conflict("/c/d/e"), Some(Conflict::Descendant));
+        assert_eq!(conflict("/ab"), None);
+        assert_eq!(conflict("/a/c"), None);
+        assert_eq!(conflict("c"), None);
+    }
+
+    #[test]
+    pub fn case_sensitive_policy() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::Sensitive);
+        trie.insert(U8PathBuf::from("/Data/Saves"), 1);
+
+        assert_eq!(trie.case_policy(), CasePolicy::Sensitive);
+        assert_eq!(trie.get(&U8PathBuf::from("/Data/Saves")), Some(&1));
+        assert_eq!(trie.get(&U8PathBuf::from("/data/saves")), None);
+        assert_eq!(trie.insert(U8PathBuf::from("/data/saves"), 2), None);
+        assert_eq!(trie.len(), 2);
+        assert_eq!(
+            PathTrie::<U8PathBuf, ()>::new().case_policy(),
+            CasePolicy::Sensitive
+        );
+    }
+
+    #[test]
+    pub fn ascii_insensitive_policy() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::AsciiInsensitive);
+        trie.insert(U8PathBuf::from("/Data//Saves"), 1);
+
+        assert_eq!(trie.get(&U8PathBuf::from("/DATA/saves")), Some(&1));
+        assert_eq!(trie.insert(U8PathBuf::from("/data/SAVES"), 2), Some(1));
+        assert_eq!(trie.len(), 1);
+        *trie.entry(U8PathBuf::from("/dAtA/sAvEs")).or_insert(0) += 1;
+        assert_eq!(trie.get(&U8PathBuf::from("/data/saves")), Some(&3));
+
+        // whole keys keep the spelling they were first inserted with
+        let (key, _) = trie
+            .find_ancestor(&U8PathBuf::from("/DATA/SAVES/slot1"), |_| true)
+            .unwrap();
+        assert_eq!(key.as_str(), "/Data/Saves");
+        let (mount, _, rest) = trie
+            .resolve_mount(&U8PathBuf::from("/data/saves/Slot1"))
+            .unwrap();
+        assert_eq!(
+            (mount.as_str(), rest.as_str()),
+            ("/Data/Saves".into(), "Slot1".into())
+        );
+        let prefix = trie.longest_prefix(U8PathBuf::from("/DATA/Saves/x"));
+        assert_eq!(prefix.as_str(), "/Data/Saves");
+        let prefix = trie.longest_prefix(U8PathBuf::from("/data/other"));
+        assert_eq!(prefix.as_str(), "/Data");
+
+        // only ASCII letters are folded
+        trie.insert(U8PathBuf::from("/Äpfel"), 4);
+        assert_eq!(trie.get(&U8PathBuf::from("/äpfel")), None);
+        assert_eq!(trie.get(&U8PathBuf::from("/ÄPFEL")), Some(&4));
+
+        assert_eq!(trie.remove(&U8PathBuf::from("/DATA/SAVES")), Some(3));
+        assert_eq!(
+            trie.drain().collect::<Vec<_>>(),
+            [(U8PathBuf::from("/Äpfel"), 4)]
+        );
+    }
+
+    #[test]
+    pub fn unicode_insensitive_policy() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::UnicodeInsensitive);
+        trie.insert(U16PathBuf::from("/Äpfel"), 1);
+        trie.insert(U16PathBuf::from("/Äpfel/Ω"), 2);
+
+        assert_eq!(trie.get(&U16PathBuf::from(r"\äPFEL")), Some(&1));
+        assert!(trie.contains_key(&U16PathBuf::from("/ÄPFEL/ω")));
+        let ancestors = trie
+            .ancestors_in_trie(&U16PathBuf::from("/äpfel/ω/Datei"))
+            .map(|(path, value)| (path.as_str().to_string().unwrap(), *value))
+            .collect::<Vec<_>>();
+        assert_eq!(ancestors, [("/Äpfel".into(), 1), ("/Äpfel/Ω".into(), 2)]);
+        let prefix = trie.longest_prefix(U16PathBuf::from("/äPFEL/ω/Datei"));
+        assert_eq!(prefix.as_str().to_string().unwrap(), "/Äpfel/Ω");
+
+        let mut trie = PathTrie::with_case_policy(CasePolicy::UnicodeInsensitive);
+        trie.insert(U8PathBuf::from("/Ωmega"), 1);
+        assert_eq!(trie.get(&U8PathBuf::from("/ωMEGA")), Some(&1));
+        trie.retain(|path, _| path.as_str() != "/Ωmega");
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    pub fn longest_prefix_component_boundary() {
+        let mut trie = PathTrie::new();
+        trie.insert(U8PathBuf::from("/a/b"), 1);
+        trie.insert(U8PathBuf::from("/hello"), 2);
+        assert_eq!(trie.longest_prefix(U8PathBuf::from("/a/bc")).as_str(), "/a");
+        assert_eq!(
+            trie.longest_prefix(U8PathBuf::from("/a/b/c")).as_str(),
+            "/a/b"
+        );
+        assert_eq!(
+            trie.longest_prefix(U8PathBuf::from("/a/b")).as_str(),
+            "/a/b"
+        );
+        assert_eq!(
+            trie.longest_prefix(U8PathBuf::from("/hellothere")).as_str(),
+            ""
+        );
+        let query = U8Path::from_str(BStr::new(r"/a\bc"));
+        assert_eq!(trie.longest_prefix_ref(query).as_str(), "/a");
+
+        let mut trie = PathTrie::new();
+        trie.insert(U16PathBuf::from("/a/b"), 1);
+        let pref = trie.longest_prefix(U16PathBuf::from("/a/bc"));
+        assert_eq!(pref.as_str(), u16str!("/a"));
+        let pref = trie.longest_prefix(U16PathBuf::from(r"\a\b\c"));
+        assert_eq!(pref.as_str(), u16str!("/a/b"));
+
+        let mut trie = PathTrie::with_case_policy(CasePolicy::AsciiInsensitive);
+        trie.insert(U8PathBuf::from("/Hello"), 1);
+        trie.insert(U8PathBuf::from("/A/B"), 2);
+        let pref = trie.longest_prefix(U8PathBuf::from("/HELLOthere"));
+        assert_eq!(pref.as_str(), "");
+        let pref = trie.longest_prefix(U8PathBuf::from("/a/bc"));
+        assert_eq!(pref.as_str(), "/A");
+    }
+
+    #[test]
+    pub fn sorted_vec_with_policy() {
+        let mut trie = PathTrie::with_case_policy(CasePolicy::AsciiInsensitive);
+        trie.insert(U8PathBuf::from("/Data/Saves"), 1);
+        trie.insert(U8PathBuf::from("/data/config"), 2);
+
+        let entries = trie.to_sorted_vec();
+        assert_eq!(entries[0].0, U8PathBuf::from("/Data/Saves"));
+
+        let rebuilt = PathTrie::from_sorted_vec_with_policy(entries, trie.case_policy());
+        assert_eq!(rebuilt.case_policy(), CasePolicy::AsciiInsensitive);
+        assert_eq!(rebuilt.len(), trie.len());
+        assert_eq!(rebuilt.get(&U8PathBuf::from("/DATA/SAVES")), Some(&1));
+        assert_eq!(
+            rebuilt.longest_prefix(U8PathBuf::from("/data/saves/slot1")),
+            U8Path::from_str(BStr::new(b"/Data/Saves"))
+        );
+        assert_eq!(rebuilt.to_sorted_vec(), trie.to_sorted_vec());
+    }
 }
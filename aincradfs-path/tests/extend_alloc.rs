@@ -0,0 +1,88 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use aincradfs_path::path::{PathOwned, U16PathBuf, U8PathBuf};
+use bstr::BStr;
+use widestring::U16String;
+
+// Counts allocations made on the current thread, so the test harness allocating
+// on others does not skew the numbers.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+const NAMES: [&str; 5] = ["mnt", "some", "deeply", "nested", "directory"];
+
+#[test]
+pub fn extend_allocates_less_than_push_u8() {
+    let many = NAMES
+        .repeat(20)
+        .into_iter()
+        .map(BStr::new)
+        .collect::<Vec<_>>();
+    assert_eq!(many.len(), 100);
+
+    let push = allocations(|| {
+        let mut path = U8PathBuf::from("/");
+        for name in &many {
+            path.push(name);
+        }
+    });
+    let extend = allocations(|| {
+        let mut path = U8PathBuf::from("/");
+        path.extend(many.iter().copied());
+    });
+    assert!(extend < push, "extend: {extend}, push: {push}");
+}
+
+#[test]
+pub fn extend_allocates_less_than_push_u16() {
+    let many = NAMES
+        .repeat(20)
+        .into_iter()
+        .map(U16String::from_str)
+        .collect::<Vec<_>>();
+
+    let push = allocations(|| {
+        let mut path = U16PathBuf::from("/");
+        for name in &many {
+            path.push(name);
+        }
+    });
+    let extend = allocations(|| {
+        let mut path = U16PathBuf::from("/");
+        path.extend(many.iter().map(|name| name.as_ustr()));
+    });
+    assert!(extend < push, "extend: {extend}, push: {push}");
+}